@@ -15,8 +15,14 @@
 *   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use std::env;
+
 fn main() {
-    let mut res = winres::WindowsResource::new();
-    res.set_icon("resources/icon.ico");
-    res.compile().unwrap();
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows")
+        && env::var_os("CARGO_FEATURE_GUI_NWG").is_some()
+    {
+        let mut res = winres::WindowsResource::new();
+        res.set_icon("resources/icon.ico");
+        res.compile().unwrap();
+    }
 }
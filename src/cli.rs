@@ -0,0 +1,330 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use rsautoclicker::input_backend::{self, InputBackend, LoggingBackend};
+use rsautoclicker::keyboard_hook::Callback;
+use rsautoclicker::settings::MAX_WHEEL_DELTA;
+use rsautoclicker::{
+    Binding, ClickType, Clicker, ClickerEvent, Delivery, Hotkey, HotkeyAction, KeyEvent,
+    KeyboardHook, MouseHook, Rate, Settings, StopReason,
+};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+const USAGE: &str = "\
+Usage: rsautoclicker [OPTIONS]
+
+Runs the clicker without the GUI until it is interrupted. Without a hotkey or
+start key clicking starts immediately and the program also exits when a limit
+is reached.
+
+Options:
+    --delay <MS>        Time between click starts in milliseconds [default: 100]
+    --cps <RATE>        Clicks per second instead of a delay, e.g. 7.5
+    --duration <MS>     Duration of a click in milliseconds [default: 0]
+    --button <BUTTON>   What to click: left, right, middle, x1, x2, double,
+                        triple, wheel or horizontal-wheel [default: left]
+    --wheel-delta <N>   Wheel ticks per click, up to 255, negative values
+                        scroll down or left [default: 1]
+    --hotkey <KEY>      Key that controls the clicker, e.g. F6 or Ctrl+Alt+F6.
+                        LCtrl, RShift etc. require one side of a modifier,
+                        MButton, XButton1 and XButton2 are mouse buttons
+    --mode <MODE>       What the hotkey does: toggle, hold or one-shot
+                        [default: toggle]
+    --start-key <KEY>   Key that starts the clicker
+    --stop-key <KEY>    Key that stops the clicker
+    --pause-key <KEY>   Key that pauses and resumes the clicker
+    --panic-key <KEY>   Key that always stops the clicker
+    --consume           Hide the hotkeys from the other applications
+    --repeat            Act on the auto-repeats of held hotkeys as well
+    --physical-only     Ignore hotkeys injected by other programs
+    --target <X,Y>      Click at a point of the screen, X,Y*N clicks it N
+                        times in a row. Repeat to cycle through points
+    --order <ORDER>     Order of the points: sequential, ping-pong or random
+                        [default: sequential]
+    --window <RULE>     Make the points relative to the client area of a
+                        window, e.g. process:game.exe; title:Level 1. Also
+                        class:<CLASS>, text without a name is a title.
+                        Clicking pauses while the window is missing
+    --delivery <MODE>   How clicks reach the --window: global moves the pointer
+                        and injects them, window posts them to the window
+                        without moving the pointer [default: global]
+    --focus <RULE>      Only click while a window matching the rule, in the
+                        form of --window, has the focus
+    --restore-cursor    Move the pointer back when the clicker stops
+    --count <N>         Stop clicking after N clicks
+    --for <SECONDS>     Stop clicking after the given number of seconds
+    --seed <N>          Seed of the random intervals, to repeat a run exactly
+    --spin              Busy-wait the end of each delay for precise timing
+    --stats             Print the achieved rate and jitter on exit
+    --dry-run           Print the events instead of injecting them
+    -h, --help          Print this help
+
+Delays and durations are either fixed (100), uniform between two bounds
+(80..120) or normal with a mean and standard deviation (100~15), clamped to
+three standard deviations or to the given bounds (100~15/90..150).";
+
+struct Options {
+    settings: Settings,
+    dry_run: bool,
+    stats: bool,
+}
+
+enum Event {
+    Interrupted,
+    Stopped(StopReason),
+}
+
+fn parse_value<T: std::str::FromStr>(
+    args: &mut impl Iterator<Item = String>,
+    flag: &str,
+) -> Result<T, String> {
+    let value = args
+        .next()
+        .ok_or_else(|| format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, flag))
+}
+
+fn parse_binding(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<Binding, String> {
+    parse_value::<Hotkey>(args, flag).map(Binding::from)
+}
+
+/// Parses the command line, `None` means that help was requested.
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        settings: Default::default(),
+        dry_run: false,
+        stats: false,
+    };
+    let mut consume = false;
+    let mut repeat = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--delay" => options.settings.click_rate = Rate::Period(parse_value(&mut args, &arg)?),
+            "--cps" => {
+                let clicks_per_second: f64 = parse_value(&mut args, &arg)?;
+                if !(clicks_per_second.is_finite() && clicks_per_second > 0.0) {
+                    return Err(format!("invalid value '{}' for {}", clicks_per_second, arg));
+                }
+                options.settings.click_rate = Rate::ClicksPerSecond(clicks_per_second);
+            }
+            "--duration" => options.settings.click_duration = parse_value(&mut args, &arg)?,
+            "--button" => options.settings.click_type = parse_value::<ClickType>(&mut args, &arg)?,
+            "--wheel-delta" => {
+                let wheel_delta: i32 = parse_value(&mut args, &arg)?;
+                if wheel_delta == 0 || !(-MAX_WHEEL_DELTA..=MAX_WHEEL_DELTA).contains(&wheel_delta)
+                {
+                    return Err(format!("invalid value '{}' for {}", wheel_delta, arg));
+                }
+                options.settings.wheel_delta = wheel_delta;
+            }
+            "--mode" => options.settings.activation_mode = parse_value(&mut args, &arg)?,
+            "--hotkey" => options.settings.activation_key = Some(parse_binding(&mut args, &arg)?),
+            "--start-key" => options.settings.start_key = Some(parse_binding(&mut args, &arg)?),
+            "--stop-key" => options.settings.stop_key = Some(parse_binding(&mut args, &arg)?),
+            "--pause-key" => options.settings.pause_key = Some(parse_binding(&mut args, &arg)?),
+            "--panic-key" => options.settings.panic_key = Some(parse_binding(&mut args, &arg)?),
+            "--consume" => consume = true,
+            "--repeat" => repeat = true,
+            "--physical-only" => options.settings.physical_keys_only = true,
+            "--target" => options.settings.targets.push(parse_value(&mut args, &arg)?),
+            "--order" => options.settings.target_order = parse_value(&mut args, &arg)?,
+            "--window" => options.settings.target_window = Some(parse_value(&mut args, &arg)?),
+            "--delivery" => options.settings.delivery = parse_value(&mut args, &arg)?,
+            "--focus" => options.settings.focus_window = Some(parse_value(&mut args, &arg)?),
+            "--restore-cursor" => options.settings.restore_cursor = true,
+            "--count" => options.settings.click_limit = Some(parse_value(&mut args, &arg)?),
+            "--for" => {
+                let seconds: f64 = parse_value(&mut args, &arg)?;
+                options.settings.time_limit = Some(
+                    Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| format!("invalid value '{}' for {}", seconds, arg))?,
+                );
+            }
+            "--seed" => options.settings.random_seed = Some(parse_value(&mut args, &arg)?),
+            "--spin" => options.settings.spin_wait = true,
+            "--stats" => options.stats = true,
+            "--dry-run" => options.dry_run = true,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    if options.settings.delivery == Delivery::Window && options.settings.target_window.is_none() {
+        return Err("--delivery window needs a --window to post to".to_string());
+    }
+    for action in HotkeyAction::iter() {
+        if let Some(binding) = options.settings.binding_mut(action) {
+            binding.consume = consume;
+            binding.ignore_repeat = !repeat;
+        }
+    }
+    Ok(Some(options))
+}
+
+/// Runs the headless clicker and returns the process exit code.
+pub fn run(args: impl Iterator<Item = String>) -> i32 {
+    let options = match parse(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return 0;
+        }
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            return 2;
+        }
+    };
+
+    let mut settings = options.settings.clone();
+    if options.dry_run {
+        // Posting to the window would bypass the logging backend.
+        settings.delivery = Delivery::Global;
+    }
+    let backend: Box<dyn InputBackend> = if options.dry_run {
+        Box::new(LoggingBackend::new())
+    } else {
+        match input_backend::default_backend(!settings.targets.is_empty()) {
+            Ok(backend) => backend,
+            Err(error) => {
+                eprintln!("error: {}", error);
+                return 1;
+            }
+        }
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    if let Err(error) = ctrlc::set_handler({
+        let sender = sender.clone();
+        move || {
+            let _ = sender.send(Event::Interrupted);
+        }
+    }) {
+        eprintln!("error: {}", error);
+        return 1;
+    }
+
+    let clicker = Clicker::with_backend(settings, backend);
+    clicker.set_listener(Some(Arc::new(move |event| match event {
+        ClickerEvent::WindowLost => eprintln!("waiting for the target window"),
+        ClickerEvent::FocusLost => eprintln!("paused until the window is focused"),
+        ClickerEvent::Stopped(reason) => {
+            let _ = sender.send(Event::Stopped(reason));
+        }
+        _ => {}
+    })));
+    let hotkeys: Vec<Hotkey> = HotkeyAction::iter()
+        .filter_map(|action| options.settings.binding(action))
+        .map(|binding| binding.hotkey)
+        .collect();
+    let callback: Callback = Arc::new({
+        let clicker = clicker.clone();
+        move |event: KeyEvent| clicker.handle_key_event(&event)
+    });
+    let _keyboard_hook = if !hotkeys.is_empty() {
+        let mut keyboard_hook = match KeyboardHook::new(callback.clone()) {
+            Ok(keyboard_hook) => keyboard_hook,
+            Err(error) => {
+                eprintln!("error: {}", error);
+                return 1;
+            }
+        };
+        keyboard_hook.set_consumed_hotkeys(options.settings.consumed_hotkeys());
+        keyboard_hook.start();
+        Some(keyboard_hook)
+    } else {
+        None
+    };
+    let _mouse_hook = if hotkeys.iter().any(|hotkey| hotkey.key.is_mouse_button()) {
+        let mut mouse_hook = match MouseHook::new(callback) {
+            Ok(mouse_hook) => mouse_hook,
+            Err(error) => {
+                eprintln!("error: {}", error);
+                return 1;
+            }
+        };
+        mouse_hook.start();
+        Some(mouse_hook)
+    } else {
+        None
+    };
+    // Without a way to start the clicker again, the program runs for as
+    // long as a single run.
+    let can_restart =
+        options.settings.activation_key.is_some() || options.settings.start_key.is_some();
+    if !can_restart {
+        clicker.start();
+    }
+
+    let exit_code = loop {
+        match receiver.recv() {
+            Ok(Event::Stopped(StopReason::BackendFailed)) => {
+                if options.settings.target_window.is_some()
+                    || options.settings.focus_window.is_some()
+                {
+                    eprintln!("error: cannot inject input or look up windows, clicking stopped");
+                } else {
+                    eprintln!("error: cannot inject input, clicking stopped");
+                }
+                break 1;
+            }
+            Ok(Event::Stopped(_)) if !can_restart => break 0,
+            Ok(Event::Stopped(_)) => {}
+            Ok(Event::Interrupted) | Err(_) => break 0,
+        }
+    };
+    clicker.stop();
+    if options.stats {
+        let timing = clicker.timing();
+        eprintln!(
+            "{} clicks at {:.2} clicks/s, jitter {:.3} ms, max lateness {:.3} ms",
+            timing.clicks,
+            timing.rate,
+            timing.jitter.as_secs_f64() * 1000.0,
+            timing.max_lateness.as_secs_f64() * 1000.0
+        );
+    }
+    exit_code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Option<Options>, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn window_delivery_needs_a_window() {
+        assert!(parse_args(&["--delivery", "window"]).is_err());
+        let options = parse_args(&["--delivery", "window", "--window", "Notepad"])
+            .unwrap()
+            .unwrap();
+        assert!(options.settings.delivery == Delivery::Window);
+    }
+    #[test]
+    fn wheel_delta_is_bounded() {
+        for wheel_delta in ["0", "256", "-256", "-2147483648"] {
+            assert!(parse_args(&["--wheel-delta", wheel_delta]).is_err());
+        }
+        let options = parse_args(&["--wheel-delta", "-255"]).unwrap().unwrap();
+        assert_eq!(options.settings.wheel_delta, -MAX_WHEEL_DELTA);
+    }
+}
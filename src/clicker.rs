@@ -15,33 +15,107 @@
 *   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::settings::{ClickType, Settings};
-use std::sync::{mpsc, Arc, Mutex};
+//! The clicker thread and its controls.
+
+use crate::error::Error;
+use crate::input_backend::{self, InputBackend, MouseButton, ScrollAxis, WindowBackend};
+use crate::keyboard_hook::KeyEvent;
+use crate::scheduler::{self, Scheduler, Timing};
+use crate::settings::{
+    ActivationMode, ClickType, Delivery, HotkeyAction, Settings, MAX_WHEEL_DELTA,
+};
+use crate::target::TargetCycle;
+use crate::window::{self, WindowInfo, WindowMatch};
+use lazy_static::lazy_static;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, Weak};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
 
 macro_rules! status_internal {
-    ($mutex_guard:expr) => {
-        if $mutex_guard.is_some() {
-            ClickerStatus::Clicking
-        } else {
-            ClickerStatus::Idle
+    ($self:expr, $mutex_guard:expr) => {
+        match $mutex_guard.as_ref() {
+            Some((_, join_handle)) if !join_handle.is_finished() => {
+                if $self.is_paused.load(Ordering::SeqCst) {
+                    ClickerStatus::Paused
+                } else {
+                    ClickerStatus::Clicking
+                }
+            }
+            _ => ClickerStatus::Idle,
         }
     };
 }
 
+/// Interval at which a paused clicker looks for its target window, or checks
+/// the focus, again.
+const WINDOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+lazy_static! {
+    /// Every clicker that was created, for `Clicker::stop_all`.
+    static ref CLICKERS: Mutex<Vec<Weak<Clicker>>> = Mutex::new(Vec::new());
+}
+
+/// Whether a `Clicker` is running.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum ClickerStatus {
     Clicking,
+    /// Running without clicking until resumed.
+    Paused,
     Idle,
 }
 
+/// Why a run of the clicker ended.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum StopReason {
+    /// `Clicker::stop` was called.
+    Requested,
+    /// `Settings::click_limit` clicks were made.
+    ClickLimitReached,
+    /// `Settings::time_limit` has passed since the clicker was started.
+    TimeLimitReached,
+    /// The input backend failed to inject a click, or the windows could not
+    /// be looked up.
+    BackendFailed,
+}
+
+/// Change of the clicker state, passed to its listener.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ClickerEvent {
+    Started,
+    Paused,
+    Resumed,
+    /// No window matches `Settings::target_window`, the clicker pauses until
+    /// one does.
+    WindowLost,
+    /// The window of `Settings::focus_window` lost the focus, the clicker
+    /// pauses until it has it again.
+    FocusLost,
+    /// The run ended, the clicker is idle.
+    Stopped(StopReason),
+}
+
+/// Function called on the clicker thread for every `ClickerEvent`.
+pub type Listener = Arc<dyn Fn(ClickerEvent) + Send + Sync + 'static>;
+
 enum ClickerMessage {
     Stop,
+    Pause,
+    Resume,
 }
 
+/// Clicks on a background thread while started, following its `Settings`.
 pub struct Clicker {
-    thread_info: Mutex<Option<(mpsc::Sender<ClickerMessage>, thread::JoinHandle<()>)>>,
+    thread_info: Mutex<Option<(mpsc::Sender<ClickerMessage>, thread::JoinHandle<StopReason>)>>,
     settings: Mutex<Settings>,
+    backend: Box<dyn InputBackend>,
+    listener: Mutex<Option<Listener>>,
+    timing: Mutex<Timing>,
+    is_paused: AtomicBool,
 }
 
 impl Drop for Clicker {
@@ -51,73 +125,816 @@ impl Drop for Clicker {
 }
 
 impl Clicker {
-    pub fn new(settings: Settings) -> Arc<Self> {
-        Arc::new(Clicker {
+    /// Creates a clicker that injects input through the native backend of
+    /// the platform, one that can move the pointer when there are targets.
+    pub fn new(settings: Settings) -> Result<Arc<Self>, Error> {
+        let backend =
+            input_backend::default_backend(!settings.targets.is_empty()).map_err(Error::Backend)?;
+        Ok(Self::with_backend(settings, backend))
+    }
+
+    /// Creates a clicker that injects input through `backend`.
+    pub fn with_backend(settings: Settings, backend: Box<dyn InputBackend>) -> Arc<Self> {
+        let clicker = Arc::new(Clicker {
             thread_info: Mutex::new(None),
             settings: Mutex::new(settings),
-        })
+            backend,
+            listener: Mutex::new(None),
+            timing: Mutex::new(Default::default()),
+            is_paused: AtomicBool::new(false),
+        });
+        let mut clickers = CLICKERS.lock().unwrap();
+        clickers.retain(|clicker| clicker.strong_count() > 0);
+        clickers.push(Arc::downgrade(&clicker));
+        drop(clickers);
+        clicker
     }
 
+    /// Stops every clicker of the process.
+    pub fn stop_all() {
+        let clickers: Vec<_> = CLICKERS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        for clicker in clickers {
+            clicker.stop();
+        }
+    }
+
+    /// Starts clicking unless the clicker is already running. Clicking stops
+    /// by itself when a limit of the settings is reached or the backend fails
+    /// to inject an event.
     pub fn start(self: &Arc<Self>) {
         let mut lock = self.thread_info.lock().unwrap();
-        if matches!(status_internal!(lock), ClickerStatus::Idle) {
+        if matches!(status_internal!(self, lock), ClickerStatus::Idle) {
+            self.is_paused.store(false, Ordering::SeqCst);
             let cloned_self = self.clone();
             let (sender, receiver) = mpsc::channel();
             *lock = Some((
                 sender,
-                thread::spawn(move || loop {
-                    if let Ok(message) = receiver.try_recv() {
-                        match message {
-                            ClickerMessage::Stop => break,
-                        }
-                    }
-                    let settings;
-                    {
-                        settings = cloned_self.settings.lock().unwrap().clone();
-                    }
-                    click(settings.click_type, settings.click_duration);
-                    thread::sleep(settings.click_delay);
+                thread::spawn(move || {
+                    let reason = cloned_self.run(receiver);
+                    cloned_self.notify(ClickerEvent::Stopped(reason));
+                    reason
                 }),
             ));
+            drop(lock);
+            self.notify(ClickerEvent::Started);
         }
     }
 
+    /// Stops clicking and waits for the click in progress to finish.
     pub fn stop(&self) {
-        let mut lock = self.thread_info.lock().unwrap();
-        if matches!(status_internal!(lock), ClickerStatus::Clicking) {
-            let (sender, join_handle) = lock.take().unwrap();
-            sender.send(ClickerMessage::Stop).unwrap();
-            join_handle.join().unwrap();
+        let thread_info = {
+            let mut lock = self.thread_info.lock().unwrap();
+            match status_internal!(self, lock) {
+                ClickerStatus::Clicking | ClickerStatus::Paused => lock.take(),
+                ClickerStatus::Idle => None,
+            }
+        };
+        if let Some((sender, join_handle)) = thread_info {
+            // The thread may have ended by itself in the meantime.
+            let _ = sender.send(ClickerMessage::Stop);
+            // The listener and the last reference to the clicker live on the
+            // clicker thread, which cannot wait for itself.
+            if join_handle.thread().id() != thread::current().id() {
+                join_handle.join().unwrap();
+            }
+        }
+    }
+
+    /// Starts an idle clicker or stops a running or paused one.
+    pub fn toggle(self: &Arc<Self>) {
+        match self.status() {
+            ClickerStatus::Clicking | ClickerStatus::Paused => self.stop(),
+            ClickerStatus::Idle => self.start(),
+        }
+    }
+
+    /// Suspends clicking without ending the run, so limits keep counting.
+    pub fn pause(&self) {
+        self.send_if(ClickerStatus::Clicking, ClickerMessage::Pause, true);
+    }
+
+    /// Continues a paused run.
+    pub fn resume(&self) {
+        self.send_if(ClickerStatus::Paused, ClickerMessage::Resume, false);
+    }
+
+    /// Pauses a running clicker or resumes a paused one.
+    pub fn toggle_pause(&self) {
+        match self.status() {
+            ClickerStatus::Clicking => self.pause(),
+            ClickerStatus::Paused => self.resume(),
+            ClickerStatus::Idle => {}
+        }
+    }
+
+    fn send_if(&self, status: ClickerStatus, message: ClickerMessage, is_paused: bool) {
+        let lock = self.thread_info.lock().unwrap();
+        if status_internal!(self, lock) == status {
+            self.is_paused.store(is_paused, Ordering::SeqCst);
+            let _ = lock.as_ref().unwrap().0.send(message);
+        }
+    }
+
+    /// Runs the operations of the hotkeys in the settings that match `event`.
+    /// The panic hotkey stops every clicker and takes precedence over the
+    /// other hotkeys, it is not subject to `Settings::physical_keys_only`.
+    /// Injected mouse buttons are ignored, as they may be the clicks of a
+    /// clicker.
+    pub fn handle_key_event(self: &Arc<Self>, event: &KeyEvent) {
+        if event.is_injected && event.key.is_mouse_button() {
+            return;
+        }
+        let settings = self.settings.lock().unwrap().clone();
+        let matches = |action| settings.hotkey_matches(action, event);
+        if event.is_pressed
+            && settings
+                .binding(HotkeyAction::Panic)
+                .is_some_and(|panic_key| panic_key.matches(event))
+        {
+            return Self::stop_all();
+        }
+        for action in HotkeyAction::iter() {
+            if !matches(action) {
+                continue;
+            }
+            match (action, event.is_pressed) {
+                (HotkeyAction::Activate, is_pressed) => self.activate(is_pressed),
+                (HotkeyAction::Start, true) => self.start(),
+                (HotkeyAction::Stop, true) => self.stop(),
+                (HotkeyAction::Pause, true) => self.toggle_pause(),
+                _ => {}
+            }
         }
     }
 
+    /// Handles a press or release of the activation key according to the
+    /// activation mode of the settings.
+    pub fn activate(self: &Arc<Self>, is_pressed: bool) {
+        let activation_mode = self.settings.lock().unwrap().activation_mode;
+        match (activation_mode, is_pressed) {
+            (ActivationMode::Toggle, true) => self.toggle(),
+            (ActivationMode::Hold, true) | (ActivationMode::OneShot, true) => self.start(),
+            (ActivationMode::Hold, false) => self.stop(),
+            (ActivationMode::Toggle, false) | (ActivationMode::OneShot, false) => {}
+        }
+    }
+
+    /// Replaces the settings, a running clicker picks them up on its next
+    /// click.
     pub fn set_settings(&self, settings: Settings) {
         let mut lock = self.settings.lock().unwrap();
         *lock = settings;
     }
 
+    /// Sets the function that is told when the clicker starts and stops.
+    pub fn set_listener(&self, listener: Option<Listener>) {
+        *self.listener.lock().unwrap() = listener;
+    }
+
+    /// Timing achieved by the current run, or by the last one when the
+    /// clicker is idle.
+    pub fn timing(&self) -> Timing {
+        *self.timing.lock().unwrap()
+    }
+
+    /// Reports whether the clicker thread is running and clicking.
     pub fn status(&self) -> ClickerStatus {
-        status_internal!(self.thread_info.lock().unwrap())
+        status_internal!(self, self.thread_info.lock().unwrap())
     }
-}
 
-fn click(click_type: ClickType, duration: Duration) {
-    use winapi::um::winuser;
+    fn notify(&self, event: ClickerEvent) {
+        let listener = self.listener.lock().unwrap().clone();
+        if let Some(listener) = listener {
+            listener(event);
+        }
+    }
 
-    let (first_event_type, second_event_type) = match click_type {
-        ClickType::Left => (winuser::MOUSEEVENTF_LEFTDOWN, winuser::MOUSEEVENTF_LEFTUP),
-        ClickType::Right => (winuser::MOUSEEVENTF_RIGHTDOWN, winuser::MOUSEEVENTF_RIGHTUP),
-        ClickType::Middle => (
-            winuser::MOUSEEVENTF_MIDDLEDOWN,
-            winuser::MOUSEEVENTF_MIDDLEUP,
-        ),
+    /// Body of the clicker thread.
+    /// Clicks until the run ends, then moves the pointer back if the settings
+    /// ask for it.
+    fn run(&self, receiver: mpsc::Receiver<ClickerMessage>) -> StopReason {
+        let restored_position = {
+            let settings = self.settings.lock().unwrap();
+            if settings.restore_cursor
+                && !settings.targets.is_empty()
+                && settings.delivery != Delivery::Window
+            {
+                self.backend.cursor_position().ok()
+            } else {
+                None
+            }
+        };
+        let reason = self.click_until_stopped(receiver);
+        if let Some((x, y)) = restored_position {
+            let _ = self.backend.move_to(x, y);
+        }
+        reason
+    }
+
+    fn click_until_stopped(&self, receiver: mpsc::Receiver<ClickerMessage>) -> StopReason {
+        let started = Instant::now();
+        let mut scheduler = Scheduler::new();
+        *self.timing.lock().unwrap() = scheduler.timing();
+        let mut clicks = 0;
+        let mut rng = match self.settings.lock().unwrap().random_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut targets = TargetCycle::new();
+        // Window found by the last lookup and the rule it matched, which is
+        // clicked until it is gone instead of listing the windows again.
+        let mut matched_window: Option<(WindowMatch, WindowInfo)> = None;
+        loop {
+            let settings;
+            {
+                settings = self.settings.lock().unwrap().clone();
+            }
+            // A time limit too long for an `Instant` is never reached.
+            let deadline = settings
+                .time_limit
+                .and_then(|time_limit| started.checked_add(time_limit));
+            let click_limit = match settings.activation_mode {
+                ActivationMode::OneShot if settings.time_limit.is_none() => {
+                    settings.click_limit.or(Some(1))
+                }
+                _ => settings.click_limit,
+            };
+            if click_limit.is_some_and(|limit| clicks >= limit) {
+                return StopReason::ClickLimitReached;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return StopReason::TimeLimitReached;
+            }
+
+            if let Some(rule) = &settings.focus_window {
+                let has_focus = || {
+                    Ok(window::foreground()?
+                        .filter(|window| rule.matches(window))
+                        .map(|_| ()))
+                };
+                if let Err(reason) = self.wait_for(
+                    &receiver,
+                    &mut scheduler,
+                    deadline,
+                    ClickerEvent::FocusLost,
+                    has_focus,
+                ) {
+                    return reason;
+                }
+            }
+            let cached = matched_window
+                .take()
+                .filter(|(rule, _)| settings.target_window.as_ref() == Some(rule))
+                .and_then(|(_, window)| match window::client_origin(&window) {
+                    Ok(Some(origin)) => Some((window, origin)),
+                    _ => None,
+                });
+            let found = match (&settings.target_window, cached) {
+                (Some(_), Some(found)) => Some(found),
+                (Some(rule), None) => {
+                    let find_window = || match window::find(rule)? {
+                        Some(window) => {
+                            Ok(window::client_origin(&window)?.map(|origin| (window, origin)))
+                        }
+                        None => Ok(None),
+                    };
+                    match self.wait_for(
+                        &receiver,
+                        &mut scheduler,
+                        deadline,
+                        ClickerEvent::WindowLost,
+                        find_window,
+                    ) {
+                        Ok(found) => Some(found),
+                        Err(reason) => return reason,
+                    }
+                }
+                (None, _) => None,
+            };
+            if let (Some(rule), Some((window, _))) = (&settings.target_window, &found) {
+                matched_window = Some((rule.clone(), window.clone()));
+            }
+            // Input posted to the window takes client coordinates, global
+            // input screen coordinates.
+            let is_posted = settings.delivery == Delivery::Window && found.is_some();
+            let window_backend;
+            let (backend, origin): (&dyn InputBackend, _) = match (settings.delivery, found) {
+                (Delivery::Window, Some((window, origin))) => {
+                    window_backend = WindowBackend::new(&window);
+                    // Without targets the input goes to where the pointer
+                    // is, as global input does.
+                    if settings.targets.is_empty() {
+                        let moved = self
+                            .backend
+                            .cursor_position()
+                            .and_then(|(x, y)| window_backend.move_to(x - origin.0, y - origin.1));
+                        if moved.is_err() {
+                            return StopReason::BackendFailed;
+                        }
+                    }
+                    (&window_backend, (0, 0))
+                }
+                (_, Some((_, origin))) => (self.backend.as_ref(), origin),
+                (_, None) => (self.backend.as_ref(), (0, 0)),
+            };
+
+            // A click that does not reach the window is not counted and
+            // leaves the targets where they are.
+            let click_started = Instant::now();
+            let mut next_targets = targets;
+            let target = next_targets.next(&settings.targets, settings.target_order, &mut rng);
+            let result = match target {
+                Some(target) => backend.move_to(origin.0 + target.x, origin.1 + target.y),
+                None => Ok(()),
+            }
+            .and_then(|_| {
+                click(
+                    backend,
+                    settings.click_type,
+                    settings.click_duration.sample(&mut rng),
+                    settings
+                        .wheel_delta
+                        .clamp(-MAX_WHEEL_DELTA, MAX_WHEEL_DELTA),
+                )
+            });
+            match result {
+                // The window closed after it was looked up, which is waited
+                // for like a window that is not found.
+                Err(error) if is_posted && error.kind() == io::ErrorKind::NotFound => {
+                    matched_window = None;
+                    continue;
+                }
+                Err(_) => return StopReason::BackendFailed,
+                Ok(()) => {}
+            }
+            scheduler.tick(click_started);
+            *self.timing.lock().unwrap() = scheduler.timing();
+            targets = next_targets;
+            clicks += 1;
+            if click_limit == Some(clicks) {
+                return StopReason::ClickLimitReached;
+            }
+
+            scheduler.advance(settings.click_rate.period().sample(&mut rng));
+            let wake_up = match (scheduler.deadline(), deadline) {
+                (Some(wake_up), Some(deadline)) => Some(wake_up.min(deadline)),
+                (wake_up, deadline) => wake_up.or(deadline),
+            };
+            if let Some(reason) = self.wait(
+                &receiver,
+                &mut scheduler,
+                wake_up,
+                deadline,
+                settings.spin_wait,
+            ) {
+                return reason;
+            }
+        }
+    }
+
+    /// Sleeps until `wake_up`, or until stopped when it is `None`, and handles
+    /// the messages that arrive meanwhile. A pause lasts until the clicker is
+    /// resumed, stopped or `deadline` passes. Returns the reason to end the
+    /// run if there is one.
+    fn wait(
+        &self,
+        receiver: &mpsc::Receiver<ClickerMessage>,
+        scheduler: &mut Scheduler,
+        wake_up: Option<Instant>,
+        deadline: Option<Instant>,
+        spin_wait: bool,
+    ) -> Option<StopReason> {
+        let sleep_until = if spin_wait {
+            wake_up.map(|wake_up| {
+                wake_up
+                    .checked_sub(scheduler::SPIN_WINDOW)
+                    .unwrap_or(wake_up)
+            })
+        } else {
+            wake_up
+        };
+        let mut is_paused = false;
+        loop {
+            let timeout = if is_paused { deadline } else { sleep_until };
+            let message = match timeout {
+                Some(timeout) => {
+                    receiver.recv_timeout(timeout.saturating_duration_since(Instant::now()))
+                }
+                None => receiver
+                    .recv()
+                    .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            };
+            match message {
+                Ok(ClickerMessage::Stop) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Some(StopReason::Requested)
+                }
+                Ok(ClickerMessage::Pause) if !is_paused => {
+                    is_paused = true;
+                    self.notify(ClickerEvent::Paused);
+                }
+                Ok(ClickerMessage::Resume) if is_paused => {
+                    self.notify(ClickerEvent::Resumed);
+                    scheduler.reset();
+                    return None;
+                }
+                Ok(_) => {}
+                // The time limit has passed during the pause.
+                Err(mpsc::RecvTimeoutError::Timeout) if is_paused => return None,
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+            }
+        }
+        if let Some(wake_up) = wake_up.filter(|_| spin_wait) {
+            scheduler::spin_until(wake_up);
+        }
+        None
+    }
+
+    /// Polls `find` until it finds a value. Meanwhile the clicker is paused
+    /// after telling `lost` to the listener, and resumes by itself once the
+    /// value is found. Pausing and resuming by hand have no effect until
+    /// then.
+    fn wait_for<T>(
+        &self,
+        receiver: &mpsc::Receiver<ClickerMessage>,
+        scheduler: &mut Scheduler,
+        deadline: Option<Instant>,
+        lost: ClickerEvent,
+        mut find: impl FnMut() -> io::Result<Option<T>>,
+    ) -> Result<T, StopReason> {
+        let mut is_waiting = false;
+        loop {
+            match find() {
+                Ok(Some(value)) => {
+                    if is_waiting {
+                        self.is_paused.store(false, Ordering::SeqCst);
+                        self.notify(ClickerEvent::Resumed);
+                        scheduler.reset();
+                    }
+                    return Ok(value);
+                }
+                Ok(None) => {}
+                Err(_) => return Err(StopReason::BackendFailed),
+            }
+            self.is_paused.store(true, Ordering::SeqCst);
+            if !is_waiting {
+                is_waiting = true;
+                self.notify(lost);
+                self.notify(ClickerEvent::Paused);
+            }
+            let timeout = match deadline {
+                Some(deadline) => {
+                    WINDOW_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => WINDOW_POLL_INTERVAL,
+            };
+            match receiver.recv_timeout(timeout) {
+                Ok(ClickerMessage::Stop) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(StopReason::Requested)
+                }
+                Ok(_) => {}
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        return Err(StopReason::TimeLimitReached);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Makes one click of `click_type`, holding buttons down for `duration`.
+fn click(
+    backend: &dyn InputBackend,
+    click_type: ClickType,
+    duration: Duration,
+    wheel_delta: i32,
+) -> io::Result<()> {
+    let (button, count) = match click_type {
+        ClickType::Left => (MouseButton::Left, 1),
+        ClickType::Right => (MouseButton::Right, 1),
+        ClickType::Middle => (MouseButton::Middle, 1),
+        ClickType::X1 => (MouseButton::X1, 1),
+        ClickType::X2 => (MouseButton::X2, 1),
+        ClickType::Double => (MouseButton::Left, 2),
+        ClickType::Triple => (MouseButton::Left, 3),
+        ClickType::Wheel => return backend.scroll(ScrollAxis::Vertical, wheel_delta),
+        ClickType::HorizontalWheel => return backend.scroll(ScrollAxis::Horizontal, wheel_delta),
+    };
+    // Each press of a multiple click follows the previous one after twice
+    // the duration, which has to stay well within the double-click interval.
+    let duration = if count > 1 {
+        duration.min(input_backend::double_click_interval() / 4)
+    } else {
+        duration
     };
 
-    unsafe {
-        winuser::mouse_event(first_event_type, 0, 0, 0, 0);
+    for i in 0..count {
+        if i > 0 {
+            thread::sleep(duration);
+        }
+        backend.press(button)?;
+        thread::sleep(duration);
+        backend.release(button)?;
     }
-    thread::sleep(duration);
-    unsafe {
-        winuser::mouse_event(second_event_type, 0, 0, 0, 0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotkey::{Binding, Hotkey, ModifierKeys};
+    use crate::input_backend::{InputEvent, RecordingBackend};
+    use crate::keys::Keys;
+    use crate::settings::Rate;
+    use std::sync::MutexGuard;
+
+    static EXCLUSIVE: Mutex<()> = Mutex::new(());
+
+    /// Keeps the tests from running at the same time, as the panic key of
+    /// one would stop the clickers of the others.
+    fn exclusive() -> MutexGuard<'static, ()> {
+        EXCLUSIVE.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn recorded_clicker(settings: Settings) -> (Arc<Clicker>, RecordingBackend) {
+        let backend = RecordingBackend::new();
+        let clicker = Clicker::with_backend(settings, Box::new(backend.clone()));
+        (clicker, backend)
+    }
+
+    fn listen(clicker: &Clicker) -> mpsc::Receiver<ClickerEvent> {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        clicker.set_listener(Some(Arc::new(move |event| {
+            let _ = sender.lock().unwrap().send(event);
+        })));
+        receiver
+    }
+
+    fn events(backend: &RecordingBackend) -> Vec<InputEvent> {
+        backend
+            .events()
+            .iter()
+            .map(|recorded| recorded.event)
+            .collect()
+    }
+
+    /// Waits until the backend has received `count` events.
+    fn wait_for_events(backend: &RecordingBackend, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while backend.events().len() < count && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn click_limit_stops_after_that_many_clicks() {
+        let _exclusive = exclusive();
+        let (clicker, backend) = recorded_clicker(Settings {
+            click_rate: Rate::Period(Duration::from_millis(1).into()),
+            click_limit: Some(3),
+            ..Default::default()
+        });
+        let receiver = listen(&clicker);
+        clicker.start();
+        let stopped = receiver
+            .iter()
+            .find(|event| matches!(event, ClickerEvent::Stopped(_)));
+        assert_eq!(
+            stopped,
+            Some(ClickerEvent::Stopped(StopReason::ClickLimitReached))
+        );
+        assert_eq!(
+            events(&backend),
+            [
+                InputEvent::Press(MouseButton::Left),
+                InputEvent::Release(MouseButton::Left)
+            ]
+            .repeat(3)
+        );
+    }
+
+    #[test]
+    fn limits_too_long_for_an_instant_are_never_reached() {
+        let _exclusive = exclusive();
+        let (clicker, backend) = recorded_clicker(Settings {
+            click_rate: Rate::Period(Duration::MAX.into()),
+            time_limit: Some(Duration::MAX),
+            ..Default::default()
+        });
+        let receiver = listen(&clicker);
+        clicker.start();
+        wait_for_events(&backend, 2);
+        thread::sleep(Duration::from_millis(50));
+        clicker.stop();
+        assert_eq!(events(&backend).len(), 2);
+        assert_eq!(
+            receiver.try_iter().last(),
+            Some(ClickerEvent::Stopped(StopReason::Requested))
+        );
+    }
+
+    #[test]
+    fn stop_ends_the_run_after_the_click_in_progress() {
+        let _exclusive = exclusive();
+        let (clicker, backend) = recorded_clicker(Settings {
+            click_rate: Rate::Period(Duration::from_secs(60).into()),
+            ..Default::default()
+        });
+        let receiver = listen(&clicker);
+        clicker.start();
+        assert_eq!(clicker.status(), ClickerStatus::Clicking);
+        wait_for_events(&backend, 2);
+        clicker.stop();
+        assert_eq!(clicker.status(), ClickerStatus::Idle);
+        assert_eq!(
+            events(&backend),
+            [
+                InputEvent::Press(MouseButton::Left),
+                InputEvent::Release(MouseButton::Left)
+            ]
+        );
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            [
+                ClickerEvent::Started,
+                ClickerEvent::Stopped(StopReason::Requested)
+            ]
+        );
+    }
+
+    #[test]
+    fn hold_mode_clicks_while_the_key_is_held() {
+        let _exclusive = exclusive();
+        let (clicker, backend) = recorded_clicker(Settings {
+            click_rate: Rate::Period(Duration::from_secs(60).into()),
+            activation_mode: ActivationMode::Hold,
+            ..Default::default()
+        });
+        let receiver = listen(&clicker);
+        clicker.activate(true);
+        assert_eq!(clicker.status(), ClickerStatus::Clicking);
+        // Repeated presses of the held key change nothing.
+        clicker.activate(true);
+        wait_for_events(&backend, 2);
+        clicker.activate(false);
+        assert_eq!(clicker.status(), ClickerStatus::Idle);
+        assert_eq!(
+            events(&backend),
+            [
+                InputEvent::Press(MouseButton::Left),
+                InputEvent::Release(MouseButton::Left)
+            ]
+        );
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            [
+                ClickerEvent::Started,
+                ClickerEvent::Stopped(StopReason::Requested)
+            ]
+        );
+    }
+
+    #[test]
+    fn one_shot_mode_clicks_once_per_press() {
+        let _exclusive = exclusive();
+        let (clicker, backend) = recorded_clicker(Settings {
+            click_rate: Rate::Period(Duration::from_millis(1).into()),
+            activation_mode: ActivationMode::OneShot,
+            ..Default::default()
+        });
+        let receiver = listen(&clicker);
+        for _ in 0..2 {
+            clicker.activate(true);
+            clicker.activate(false);
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while clicker.status() != ClickerStatus::Idle && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            [
+                ClickerEvent::Started,
+                ClickerEvent::Stopped(StopReason::ClickLimitReached)
+            ]
+            .repeat(2)
+        );
+        assert_eq!(
+            events(&backend),
+            [
+                InputEvent::Press(MouseButton::Left),
+                InputEvent::Release(MouseButton::Left)
+            ]
+            .repeat(2)
+        );
+    }
+
+    #[test]
+    fn panic_key_stops_every_clicker_before_other_hotkeys() {
+        let _exclusive = exclusive();
+        let key = |is_pressed| KeyEvent {
+            key: Keys::F6.into(),
+            is_pressed,
+            is_repeat: false,
+            is_injected: false,
+            modifiers: ModifierKeys::default(),
+        };
+        let settings = Settings {
+            click_rate: Rate::Period(Duration::from_secs(60).into()),
+            ..Default::default()
+        };
+        let (running, _) = recorded_clicker(settings.clone());
+        let (other, _) = recorded_clicker(settings.clone());
+        // The panic key is also the activation key, which would start it.
+        let (idle, backend) = recorded_clicker(Settings {
+            activation_key: Some(Binding::from(Hotkey::from(Keys::F6))),
+            panic_key: Some(Binding::from(Hotkey::from(Keys::F6))),
+            ..settings
+        });
+        running.start();
+        other.start();
+
+        idle.handle_key_event(&key(true));
+        idle.handle_key_event(&key(false));
+        for clicker in [&running, &other, &idle].iter() {
+            assert_eq!(clicker.status(), ClickerStatus::Idle);
+        }
+        assert!(backend.events().is_empty());
+    }
+
+    #[test]
+    fn multiple_clicks_press_within_the_double_click_interval() {
+        let _exclusive = exclusive();
+        for (click_type, count) in [(ClickType::Double, 2), (ClickType::Triple, 3)].iter() {
+            let (clicker, backend) = recorded_clicker(Settings {
+                click_type: *click_type,
+                click_duration: Duration::from_millis(5).into(),
+                click_limit: Some(1),
+                ..Default::default()
+            });
+            clicker.start();
+            wait_for_events(&backend, 2 * count);
+            assert_eq!(
+                events(&backend),
+                [
+                    InputEvent::Press(MouseButton::Left),
+                    InputEvent::Release(MouseButton::Left)
+                ]
+                .repeat(*count)
+            );
+            let presses: Vec<_> = backend
+                .events()
+                .into_iter()
+                .filter(|recorded| recorded.event == InputEvent::Press(MouseButton::Left))
+                .map(|recorded| recorded.timestamp)
+                .collect();
+            for pair in presses.windows(2) {
+                assert!(pair[1] - pair[0] <= input_backend::double_click_interval());
+            }
+        }
+    }
+
+    #[test]
+    fn injected_events_are_left_out_of_hotkeys() {
+        let _exclusive = exclusive();
+        let press = |key: Keys, is_injected| KeyEvent {
+            key: key.into(),
+            is_pressed: true,
+            is_repeat: false,
+            is_injected,
+            modifiers: ModifierKeys::default(),
+        };
+        let (clicker, _) = recorded_clicker(Settings {
+            click_rate: Rate::Period(Duration::from_secs(60).into()),
+            start_key: Some(Binding::from(Hotkey::from(Keys::MButton))),
+            stop_key: Some(Binding::from(Hotkey::from(Keys::F6))),
+            ..Default::default()
+        });
+        clicker.handle_key_event(&press(Keys::MButton, true));
+        assert_eq!(clicker.status(), ClickerStatus::Idle);
+        clicker.handle_key_event(&press(Keys::MButton, false));
+        assert_eq!(clicker.status(), ClickerStatus::Clicking);
+        // Injected keys count unless physical_keys_only is set.
+        clicker.handle_key_event(&press(Keys::F6, true));
+        assert_eq!(clicker.status(), ClickerStatus::Idle);
+
+        clicker.set_settings(Settings {
+            click_rate: Rate::Period(Duration::from_secs(60).into()),
+            start_key: Some(Binding::from(Hotkey::from(Keys::F6))),
+            physical_keys_only: true,
+            ..Default::default()
+        });
+        clicker.handle_key_event(&press(Keys::F6, true));
+        assert_eq!(clicker.status(), ClickerStatus::Idle);
+        clicker.handle_key_event(&press(Keys::F6, false));
+        assert_eq!(clicker.status(), ClickerStatus::Clicking);
+        clicker.stop();
+        assert_eq!(clicker.status(), ClickerStatus::Idle);
     }
 }
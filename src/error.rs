@@ -0,0 +1,57 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::error;
+use std::fmt;
+use std::io;
+
+/// Errors returned by the public API of the crate.
+#[derive(Debug)]
+pub enum Error {
+    /// The configuration directory of the current user could not be found.
+    NoConfigDirectory,
+    /// Reading or writing the configuration file failed.
+    Io(io::Error),
+    /// The settings could not be converted from or to JSON.
+    Serialization(serde_json::Error),
+    /// The input backend could not be created.
+    Backend(io::Error),
+    /// The global hotkey listener could not be installed.
+    Hook(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoConfigDirectory => write!(f, "configuration directory not found"),
+            Error::Io(error) => write!(f, "cannot access configuration file: {}", error),
+            Error::Serialization(error) => write!(f, "invalid settings: {}", error),
+            Error::Backend(error) => write!(f, "cannot create input backend: {}", error),
+            Error::Hook(error) => write!(f, "cannot install hotkey listener: {}", error),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::NoConfigDirectory => None,
+            Error::Io(error) | Error::Backend(error) | Error::Hook(error) => Some(error),
+            Error::Serialization(error) => Some(error),
+        }
+    }
+}
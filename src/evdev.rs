@@ -0,0 +1,205 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Definitions of the Linux input subsystem (`linux/input.h`, `linux/uinput.h`).
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::slice;
+
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_REL: u16 = 0x02;
+pub const EV_ABS: u16 = 0x03;
+
+pub const SYN_REPORT: u16 = 0;
+
+pub const REL_X: u16 = 0x00;
+pub const REL_Y: u16 = 0x01;
+pub const REL_HWHEEL: u16 = 0x06;
+pub const REL_WHEEL: u16 = 0x08;
+
+pub const ABS_X: u16 = 0x00;
+pub const ABS_Y: u16 = 0x01;
+
+pub const BTN_MISC: u16 = 0x100;
+pub const BTN_LEFT: u16 = 0x110;
+pub const BTN_RIGHT: u16 = 0x111;
+pub const BTN_MIDDLE: u16 = 0x112;
+pub const BTN_SIDE: u16 = 0x113;
+pub const BTN_EXTRA: u16 = 0x114;
+
+pub const KEY_MAX: u16 = 0x2ff;
+
+pub const BUS_VIRTUAL: u16 = 0x06;
+
+const IOC_WRITE: libc::c_ulong = 1;
+const IOC_READ: libc::c_ulong = 2;
+
+const fn ioc(dir: libc::c_ulong, kind: u8, nr: u8, size: usize) -> libc::c_ulong {
+    (dir << 30)
+        | ((size as libc::c_ulong) << 16)
+        | ((kind as libc::c_ulong) << 8)
+        | nr as libc::c_ulong
+}
+
+const EVDEV_IOCTL_BASE: u8 = b'E';
+const UINPUT_IOCTL_BASE: u8 = b'U';
+
+pub const fn eviocgbit(kind: u16, length: usize) -> libc::c_ulong {
+    ioc(IOC_READ, EVDEV_IOCTL_BASE, 0x20 + kind as u8, length)
+}
+
+pub const fn eviocgkey(length: usize) -> libc::c_ulong {
+    ioc(IOC_READ, EVDEV_IOCTL_BASE, 0x18, length)
+}
+
+pub const fn eviocgname(length: usize) -> libc::c_ulong {
+    ioc(IOC_READ, EVDEV_IOCTL_BASE, 0x06, length)
+}
+
+pub const EVIOCGRAB: libc::c_ulong = ioc(
+    IOC_WRITE,
+    EVDEV_IOCTL_BASE,
+    0x90,
+    mem::size_of::<libc::c_int>(),
+);
+
+pub const UI_DEV_CREATE: libc::c_ulong = ioc(0, UINPUT_IOCTL_BASE, 1, 0);
+pub const UI_DEV_DESTROY: libc::c_ulong = ioc(0, UINPUT_IOCTL_BASE, 2, 0);
+pub const UI_DEV_SETUP: libc::c_ulong = ioc(
+    IOC_WRITE,
+    UINPUT_IOCTL_BASE,
+    3,
+    mem::size_of::<libc::uinput_setup>(),
+);
+pub const UI_ABS_SETUP: libc::c_ulong = ioc(
+    IOC_WRITE,
+    UINPUT_IOCTL_BASE,
+    4,
+    mem::size_of::<libc::uinput_abs_setup>(),
+);
+pub const UI_SET_EVBIT: libc::c_ulong = ioc(
+    IOC_WRITE,
+    UINPUT_IOCTL_BASE,
+    100,
+    mem::size_of::<libc::c_int>(),
+);
+pub const UI_SET_KEYBIT: libc::c_ulong = ioc(
+    IOC_WRITE,
+    UINPUT_IOCTL_BASE,
+    101,
+    mem::size_of::<libc::c_int>(),
+);
+pub const UI_SET_RELBIT: libc::c_ulong = ioc(
+    IOC_WRITE,
+    UINPUT_IOCTL_BASE,
+    102,
+    mem::size_of::<libc::c_int>(),
+);
+pub const UI_SET_ABSBIT: libc::c_ulong = ioc(
+    IOC_WRITE,
+    UINPUT_IOCTL_BASE,
+    103,
+    mem::size_of::<libc::c_int>(),
+);
+
+/// Calls `ioctl` on `file`, converting a failure into `io::Error`. Pointer
+/// arguments are passed cast to `c_ulong`.
+pub fn ioctl(file: &File, request: libc::c_ulong, argument: libc::c_ulong) -> io::Result<()> {
+    if unsafe { libc::ioctl(file.as_raw_fd(), request as _, argument) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Returns the codes of the given event type that `device` can emit.
+pub fn supported_codes(device: &File, kind: u16) -> io::Result<Vec<u16>> {
+    read_key_bits(device, |length| eviocgbit(kind, length))
+}
+
+/// Returns the codes of the keys and buttons of `device` that are held down.
+pub fn pressed_keys(device: &File) -> io::Result<Vec<u16>> {
+    read_key_bits(device, eviocgkey)
+}
+
+/// Reads a bit array with a bit for every key code through `request`, which
+/// is given the length of the array.
+fn read_key_bits(
+    device: &File,
+    request: impl FnOnce(usize) -> libc::c_ulong,
+) -> io::Result<Vec<u16>> {
+    let mut bits = [0u8; KEY_MAX as usize / 8 + 1];
+    ioctl(
+        device,
+        request(bits.len()),
+        bits.as_mut_ptr() as libc::c_ulong,
+    )?;
+    Ok((0..=KEY_MAX)
+        .filter(|code| bits[*code as usize / 8] & (1 << (code % 8)) != 0)
+        .collect())
+}
+
+/// Returns the name that `device` reports to the kernel.
+pub fn device_name(device: &File) -> io::Result<String> {
+    let mut name = [0u8; 256];
+    ioctl(
+        device,
+        eviocgname(name.len()),
+        name.as_mut_ptr() as libc::c_ulong,
+    )?;
+    let length = name
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(name.len());
+    Ok(String::from_utf8_lossy(&name[..length]).into_owned())
+}
+
+pub fn read_event(mut file: &File) -> io::Result<libc::input_event> {
+    let mut event: libc::input_event = unsafe { mem::zeroed() };
+    file.read_exact(unsafe {
+        slice::from_raw_parts_mut(
+            &mut event as *mut libc::input_event as *mut u8,
+            mem::size_of::<libc::input_event>(),
+        )
+    })?;
+    Ok(event)
+}
+
+pub fn write_event(mut file: &File, kind: u16, code: u16, value: i32) -> io::Result<()> {
+    let event = libc::input_event {
+        time: libc::timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+        type_: kind,
+        code,
+        value,
+    };
+    file.write_all(unsafe {
+        slice::from_raw_parts(
+            &event as *const libc::input_event as *const u8,
+            mem::size_of::<libc::input_event>(),
+        )
+    })
+}
+
+pub fn write_report(file: &File) -> io::Result<()> {
+    write_event(file, EV_SYN, SYN_REPORT, 0)
+}
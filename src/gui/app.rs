@@ -17,9 +17,10 @@
 
 use super::settings_editor::SettingsEditor;
 use crate::resources::ICON;
-use crate::{keyboard_hook::KeyboardHook, settings::Settings};
 use nwd::NwgUi;
-use nwg::{GridLayout, Icon, Menu, MenuItem, TextInput, Window};
+use nwg::{GridLayout, Icon, Menu, MenuItem, Notice, TextInput, Window};
+use rsautoclicker::clicker::Listener;
+use rsautoclicker::{ActivationMode, ClickerEvent, KeyboardHook, MouseHook, Settings};
 use std::cell::RefCell;
 use std::sync::{Arc, Mutex};
 
@@ -31,6 +32,11 @@ pub struct App {
 
     keyboard_hook: Arc<Mutex<KeyboardHook>>,
 
+    mouse_hook: Arc<Mutex<MouseHook>>,
+
+    /// Event that made the clicker wait for a window, while it waits.
+    wait_reason: Arc<Mutex<Option<ClickerEvent>>>,
+
     #[nwg_control(title: "RS Autoclicker", flags: "WINDOW|VISIBLE", size: (250, 60))]
     #[nwg_events(OnWindowClose: [App::on_close], OnInit: [App::on_init])]
     window: Window,
@@ -48,6 +54,10 @@ pub struct App {
     #[nwg_control(text: "Open Tools->Options...", readonly: true, align: HTextAlign::Center)]
     #[nwg_layout_item(layout: layout, col: 0, row: 0)]
     selected_key_text_input: TextInput,
+
+    #[nwg_control(parent: window)]
+    #[nwg_events(OnNotice: [App::update])]
+    status_notice: Notice,
 }
 
 impl App {
@@ -55,19 +65,42 @@ impl App {
         settings: Settings,
         on_settings_changed: Box<dyn Fn(&Settings)>,
         keyboard_hook: Arc<Mutex<KeyboardHook>>,
+        mouse_hook: Arc<Mutex<MouseHook>>,
     ) -> Self {
         Self {
             settings: RefCell::new(settings),
             on_settings_changed,
             keyboard_hook,
+            mouse_hook,
+            wait_reason: Default::default(),
             window: Default::default(),
             tools_menu: Default::default(),
             options_menu: Default::default(),
             layout: Default::default(),
             selected_key_text_input: Default::default(),
+            status_notice: Default::default(),
         }
     }
 
+    /// Listener of the clicker that shows when it waits for its target
+    /// window or for the focus.
+    pub fn clicker_listener(&self) -> Listener {
+        let wait_reason = self.wait_reason.clone();
+        let sender = self.status_notice.sender();
+        Arc::new(move |event| {
+            match event {
+                ClickerEvent::WindowLost | ClickerEvent::FocusLost => {
+                    *wait_reason.lock().unwrap() = Some(event)
+                }
+                ClickerEvent::Resumed | ClickerEvent::Stopped(_) => {
+                    *wait_reason.lock().unwrap() = None
+                }
+                _ => return,
+            }
+            sender.notice();
+        })
+    }
+
     fn on_init(&self) {
         self.window.set_icon(Some(&Icon::from_bin(ICON).unwrap()));
 
@@ -75,11 +108,22 @@ impl App {
     }
 
     fn update(&self) {
-        if let Some(activation_key_code) = self.settings.borrow().activation_key {
-            self.selected_key_text_input.set_text(&format!(
-                "Press '{}' to toggle",
-                activation_key_code.to_string()
-            ));
+        let settings = self.settings.borrow();
+        let wait_reason = *self.wait_reason.lock().unwrap();
+        if wait_reason == Some(ClickerEvent::WindowLost) {
+            self.selected_key_text_input
+                .set_text("Waiting for the target window");
+        } else if wait_reason == Some(ClickerEvent::FocusLost) {
+            self.selected_key_text_input
+                .set_text("Paused until the window is focused");
+        } else if let Some(activation_key) = settings.activation_key {
+            let activation_key_code = activation_key.hotkey;
+            self.selected_key_text_input
+                .set_text(&match settings.activation_mode {
+                    ActivationMode::Toggle => format!("Press '{}' to toggle", activation_key_code),
+                    ActivationMode::Hold => format!("Hold '{}' to click", activation_key_code),
+                    ActivationMode::OneShot => format!("Press '{}' to click", activation_key_code),
+                });
         }
     }
 
@@ -89,6 +133,7 @@ impl App {
 
     fn on_options_menu_click(&self) {
         self.keyboard_hook.lock().unwrap().stop();
+        self.mouse_hook.lock().unwrap().stop();
         self.window.set_visible(false);
         self.settings
             .replace(SettingsEditor::show(self.settings.take()));
@@ -96,5 +141,6 @@ impl App {
         (self.on_settings_changed)(&self.settings.borrow());
         self.window.set_visible(true);
         self.keyboard_hook.lock().unwrap().start();
+        self.mouse_hook.lock().unwrap().start();
     }
 }
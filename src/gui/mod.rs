@@ -18,4 +18,95 @@
 mod app;
 mod settings_editor;
 
-pub use app::App;
+use app::App;
+use nwg::NativeUi;
+use rsautoclicker::keyboard_hook::Callback;
+use rsautoclicker::{Clicker, Hotkey, HotkeyAction, KeyEvent, KeyboardHook, MouseHook, Settings};
+use std::sync::{Arc, Mutex};
+
+/// Shows the main window and runs the clicker until it is closed.
+pub fn run() {
+    let settings = Arc::new(Mutex::new(Settings::load_or_default()));
+    let clicker = Clicker::new(settings.lock().unwrap().clone()).unwrap();
+    let callback: Callback = {
+        let clicker = clicker.clone();
+        Arc::new(move |event: KeyEvent| clicker.handle_key_event(&event))
+    };
+    let keyboard_hook = Arc::new(Mutex::new(KeyboardHook::new(callback.clone()).unwrap()));
+    let mouse_hook = Arc::new(Mutex::new(MouseHook::new(callback).unwrap()));
+    keyboard_hook
+        .lock()
+        .unwrap()
+        .set_consumed_hotkeys(settings.lock().unwrap().consumed_hotkeys());
+    keyboard_hook.lock().unwrap().start();
+    mouse_hook.lock().unwrap().start();
+    // Unlike the hooks above these keep running while the options are
+    // edited, so that the panic key always works.
+    let panic_callback: Callback = {
+        let settings = settings.clone();
+        Arc::new(move |event: KeyEvent| {
+            let settings = settings.lock().unwrap();
+            if event.is_pressed
+                && settings
+                    .binding(HotkeyAction::Panic)
+                    .is_some_and(|panic_key| panic_key.matches(&event))
+            {
+                Clicker::stop_all();
+            }
+        })
+    };
+    let panic_keyboard_hook = Arc::new(Mutex::new(
+        KeyboardHook::new(panic_callback.clone()).unwrap(),
+    ));
+    panic_keyboard_hook
+        .lock()
+        .unwrap()
+        .set_consumed_hotkeys(panic_consumed_hotkeys(&settings.lock().unwrap()));
+    panic_keyboard_hook.lock().unwrap().start();
+    let mut panic_mouse_hook = MouseHook::new(panic_callback).unwrap();
+    panic_mouse_hook.start();
+
+    nwg::init().unwrap();
+    nwg::Font::set_global_family("Segoe UI").unwrap();
+
+    let app = App::build_ui(App::new(
+        settings.lock().unwrap().clone(),
+        Box::new({
+            let settings = settings.clone();
+            let clicker = clicker.clone();
+            let keyboard_hook = keyboard_hook.clone();
+            move |changed_settings| {
+                *settings.lock().unwrap() = changed_settings.clone();
+                clicker.set_settings(changed_settings.clone());
+                keyboard_hook
+                    .lock()
+                    .unwrap()
+                    .set_consumed_hotkeys(changed_settings.consumed_hotkeys());
+                panic_keyboard_hook
+                    .lock()
+                    .unwrap()
+                    .set_consumed_hotkeys(panic_consumed_hotkeys(changed_settings));
+            }
+        }),
+        keyboard_hook.clone(),
+        mouse_hook.clone(),
+    ))
+    .unwrap();
+    clicker.set_listener(Some(app.clicker_listener()));
+
+    nwg::dispatch_thread_events();
+    clicker.set_listener(None);
+
+    settings.lock().unwrap().save().unwrap();
+}
+
+/// The panic key when it is consumed, which stays hidden from the other
+/// applications while the options are edited.
+fn panic_consumed_hotkeys(settings: &Settings) -> Vec<Hotkey> {
+    settings
+        .panic_key
+        .filter(|panic_key| panic_key.consume)
+        .map(|panic_key| panic_key.hotkey)
+        .into_iter()
+        .collect()
+}
@@ -15,13 +15,21 @@
 *   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::keyboard_hook::KeyboardHook;
 use crate::resources::ICON;
-use crate::settings::{ClickType, Settings};
-use num_traits::FromPrimitive;
 use nwd::NwgUi;
-use nwg::{Button, ComboBox, GridLayout, Icon, NativeUi, Notice, TextInput, Tooltip, Window};
-use std::cell::RefCell;
+use nwg::{
+    Button, CheckBox, CheckBoxState, ComboBox, GridLayout, Icon, NativeUi, Notice, TextInput,
+    Tooltip, Window,
+};
+use rsautoclicker::hotkey::Modifier;
+use rsautoclicker::keyboard_hook::Callback;
+use rsautoclicker::settings::MAX_WHEEL_DELTA;
+use rsautoclicker::target::{format_targets, parse_targets};
+use rsautoclicker::{
+    ActivationMode, Binding, ClickType, Delivery, Hotkey, HotkeyAction, Interval, KeyEvent,
+    KeyboardHook, Keys, MouseHook, Rate, Settings, TargetOrder, WindowMatch,
+};
+use std::cell::{Cell, RefCell};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -31,45 +39,153 @@ use strum::IntoEnumIterator;
 pub struct SettingsEditor {
     settings: Arc<Mutex<Settings>>,
 
-    is_changing_activator_key: Arc<Mutex<bool>>,
+    /// Action whose hotkey is being captured.
+    changing_hotkey: Arc<Mutex<Option<HotkeyAction>>>,
 
     keyboard_hook: RefCell<Option<KeyboardHook>>,
 
+    mouse_hook: RefCell<Option<MouseHook>>,
+
+    /// Set while one view of the rate is updated from the other.
+    is_syncing_rate: Cell<bool>,
+
     tooltip: Tooltip,
 
-    #[nwg_control(title: "RS Autoclicker Configuration", flags: "WINDOW|VISIBLE", size: (250, 200))]
+    #[nwg_control(title: "RS Autoclicker Configuration", flags: "WINDOW|VISIBLE", size: (250, 800))]
     #[nwg_events(OnWindowClose: [SettingsEditor::on_close], OnInit: [SettingsEditor::on_init])]
     window: Window,
 
-    #[nwg_layout(parent: window, max_row: Some(4), max_column: Some(1))]
+    #[nwg_layout(parent: window, max_row: Some(19), max_column: Some(4))]
     layout: GridLayout,
 
     #[nwg_control]
-    #[nwg_events(OnNotice: [SettingsEditor::on_click_activator_update_text_notice])]
-    click_activator_update_text_notice: Notice,
+    #[nwg_events(OnNotice: [SettingsEditor::on_hotkey_update_text_notice])]
+    hotkey_update_text_notice: Notice,
 
     #[nwg_control]
     #[nwg_events(OnComboxBoxSelection: [SettingsEditor::on_click_type_selected])]
-    #[nwg_layout_item(layout: layout, col: 0, row: 0)]
+    #[nwg_layout_item(layout: layout, col: 0, row: 0, col_span: 3)]
     click_type_selector: ComboBox<ClickType>,
 
-    #[nwg_control(text: &data.settings.lock().unwrap().click_delay.as_millis().to_string())]
+    #[nwg_control(text: &data.settings.lock().unwrap().wheel_delta.to_string())]
+    #[nwg_events(OnTextInput: [SettingsEditor::on_wheel_delta_changed])]
+    #[nwg_layout_item(layout: layout, col: 3, row: 0)]
+    wheel_delta_selector: TextInput,
+
+    #[nwg_control(text: &data.settings.lock().unwrap().click_rate.period().to_string())]
     #[nwg_events(OnTextInput: [SettingsEditor::on_click_delay_changed])]
-    #[nwg_layout_item(layout: layout, col: 0, row: 1)]
+    #[nwg_layout_item(layout: layout, col: 0, row: 1, col_span: 4)]
     click_delay_selector: TextInput,
 
-    #[nwg_control(text: &data.settings.lock().unwrap().click_duration.as_millis().to_string())]
+    #[nwg_control(text: &format_clicks_per_second(data.settings.lock().unwrap().click_rate.clicks_per_second()))]
+    #[nwg_events(OnTextInput: [SettingsEditor::on_clicks_per_second_changed])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 2, col_span: 4)]
+    clicks_per_second_selector: TextInput,
+
+    #[nwg_control(text: &data.settings.lock().unwrap().click_duration.to_string())]
     #[nwg_events(OnTextInput: [SettingsEditor::on_click_duration_changed])]
-    #[nwg_layout_item(layout: layout, col: 0, row: 2)]
+    #[nwg_layout_item(layout: layout, col: 0, row: 3, col_span: 4)]
     click_duration_selector: TextInput,
 
-    #[nwg_control(text: &match data.settings.lock().unwrap().activation_key{
-        Some(activation_key) => activation_key.to_string(),
-        None => "No key selected".to_string(),
-    })]
+    #[nwg_control(text: &hotkey_text(&data.settings.lock().unwrap(), HotkeyAction::Activate))]
     #[nwg_events(OnButtonClick: [SettingsEditor::on_click_activator_click])]
-    #[nwg_layout_item(layout: layout, col: 0, row: 3)]
+    #[nwg_layout_item(layout: layout, col: 0, row: 4, col_span: 3)]
     click_activator_button: Button,
+
+    #[nwg_control(text: "Consume")]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_consume_click])]
+    #[nwg_layout_item(layout: layout, col: 3, row: 4)]
+    click_activator_consume_check_box: CheckBox,
+
+    #[nwg_control]
+    #[nwg_events(OnComboxBoxSelection: [SettingsEditor::on_activation_mode_selected])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 5, col_span: 4)]
+    activation_mode_selector: ComboBox<ActivationMode>,
+
+    #[nwg_control(text: &data.settings.lock().unwrap().click_limit.map(|click_limit| click_limit.to_string()).unwrap_or_default())]
+    #[nwg_events(OnTextInput: [SettingsEditor::on_click_limit_changed])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 6, col_span: 4)]
+    click_limit_selector: TextInput,
+
+    #[nwg_control(text: &data.settings.lock().unwrap().time_limit.map(|time_limit| time_limit.as_secs_f64().to_string()).unwrap_or_default())]
+    #[nwg_events(OnTextInput: [SettingsEditor::on_time_limit_changed])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 7, col_span: 4)]
+    time_limit_selector: TextInput,
+
+    #[nwg_control(text: &hotkey_text(&data.settings.lock().unwrap(), HotkeyAction::Start))]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_start_key_click])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 8, col_span: 3)]
+    start_key_button: Button,
+
+    #[nwg_control(text: "Consume")]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_consume_click])]
+    #[nwg_layout_item(layout: layout, col: 3, row: 8)]
+    start_key_consume_check_box: CheckBox,
+
+    #[nwg_control(text: &hotkey_text(&data.settings.lock().unwrap(), HotkeyAction::Stop))]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_stop_key_click])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 9, col_span: 3)]
+    stop_key_button: Button,
+
+    #[nwg_control(text: "Consume")]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_consume_click])]
+    #[nwg_layout_item(layout: layout, col: 3, row: 9)]
+    stop_key_consume_check_box: CheckBox,
+
+    #[nwg_control(text: &hotkey_text(&data.settings.lock().unwrap(), HotkeyAction::Pause))]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_pause_key_click])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 10, col_span: 3)]
+    pause_key_button: Button,
+
+    #[nwg_control(text: "Consume")]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_consume_click])]
+    #[nwg_layout_item(layout: layout, col: 3, row: 10)]
+    pause_key_consume_check_box: CheckBox,
+
+    #[nwg_control(text: &hotkey_text(&data.settings.lock().unwrap(), HotkeyAction::Panic))]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_panic_key_click])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 11, col_span: 3)]
+    panic_key_button: Button,
+
+    #[nwg_control(text: "Consume")]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_consume_click])]
+    #[nwg_layout_item(layout: layout, col: 3, row: 11)]
+    panic_key_consume_check_box: CheckBox,
+
+    #[nwg_control(text: "Physical keys only")]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_physical_keys_only_click])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 12, col_span: 4)]
+    physical_keys_only_check_box: CheckBox,
+
+    #[nwg_control(text: &format_targets(&data.settings.lock().unwrap().targets))]
+    #[nwg_events(OnTextInput: [SettingsEditor::on_targets_changed])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 13, col_span: 4)]
+    targets_selector: TextInput,
+
+    #[nwg_control]
+    #[nwg_events(OnComboxBoxSelection: [SettingsEditor::on_target_order_selected])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 14, col_span: 4)]
+    target_order_selector: ComboBox<TargetOrder>,
+
+    #[nwg_control(text: &data.settings.lock().unwrap().target_window.as_ref().map(WindowMatch::to_string).unwrap_or_default())]
+    #[nwg_events(OnTextInput: [SettingsEditor::on_target_window_changed])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 15, col_span: 4)]
+    target_window_selector: TextInput,
+
+    #[nwg_control(text: &data.settings.lock().unwrap().focus_window.as_ref().map(WindowMatch::to_string).unwrap_or_default())]
+    #[nwg_events(OnTextInput: [SettingsEditor::on_focus_window_changed])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 16, col_span: 4)]
+    focus_window_selector: TextInput,
+
+    #[nwg_control]
+    #[nwg_events(OnComboxBoxSelection: [SettingsEditor::on_delivery_selected])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 17, col_span: 4)]
+    delivery_selector: ComboBox<Delivery>,
+
+    #[nwg_control(text: "Restore cursor position")]
+    #[nwg_events(OnButtonClick: [SettingsEditor::on_restore_cursor_click])]
+    #[nwg_layout_item(layout: layout, col: 0, row: 18, col_span: 4)]
+    restore_cursor_check_box: CheckBox,
 }
 
 impl SettingsEditor {
@@ -89,16 +205,39 @@ impl SettingsEditor {
         Tooltip::builder().build(&mut tooltip).unwrap();
         Self {
             settings: Arc::new(Mutex::new(settings)),
-            is_changing_activator_key: Arc::new(Mutex::new(false)),
+            changing_hotkey: Arc::new(Mutex::new(None)),
             keyboard_hook: RefCell::new(None),
+            mouse_hook: RefCell::new(None),
+            is_syncing_rate: Cell::new(false),
             tooltip,
             window: Default::default(),
             layout: Default::default(),
-            click_activator_update_text_notice: Default::default(),
+            hotkey_update_text_notice: Default::default(),
             click_type_selector: Default::default(),
+            wheel_delta_selector: Default::default(),
             click_delay_selector: Default::default(),
+            clicks_per_second_selector: Default::default(),
             click_duration_selector: Default::default(),
             click_activator_button: Default::default(),
+            click_activator_consume_check_box: Default::default(),
+            activation_mode_selector: Default::default(),
+            click_limit_selector: Default::default(),
+            time_limit_selector: Default::default(),
+            start_key_button: Default::default(),
+            start_key_consume_check_box: Default::default(),
+            stop_key_button: Default::default(),
+            stop_key_consume_check_box: Default::default(),
+            pause_key_button: Default::default(),
+            pause_key_consume_check_box: Default::default(),
+            panic_key_button: Default::default(),
+            panic_key_consume_check_box: Default::default(),
+            physical_keys_only_check_box: Default::default(),
+            targets_selector: Default::default(),
+            target_order_selector: Default::default(),
+            target_window_selector: Default::default(),
+            focus_window_selector: Default::default(),
+            delivery_selector: Default::default(),
+            restore_cursor_check_box: Default::default(),
         }
     }
 
@@ -113,33 +252,167 @@ impl SettingsEditor {
             }
         }
 
-        self.tooltip
-            .register(&self.click_delay_selector, "Delay between clicks, ms");
+        self.tooltip.register(
+            &self.click_delay_selector,
+            "Time between click starts, ms: 100, 80..120 or 100~15",
+        );
+        for activation_mode in ActivationMode::iter() {
+            self.activation_mode_selector.push(activation_mode);
+            if activation_mode == self.settings.lock().unwrap().activation_mode {
+                self.activation_mode_selector
+                    .set_selection(Some(self.activation_mode_selector.len() - 1));
+            }
+        }
+
+        self.tooltip.register(
+            &self.clicks_per_second_selector,
+            "Clicks per second, fractions allowed",
+        );
         self.tooltip
             .register(&self.click_type_selector, "Click type");
+        self.tooltip.register(
+            &self.wheel_delta_selector,
+            "Wheel ticks per click, up to 255, negative values scroll down or left",
+        );
+        self.tooltip.register(
+            &self.click_duration_selector,
+            "Duration of click, ms: 0, 10..30 or 20~5",
+        );
+        self.tooltip.register(
+            &self.click_activator_button,
+            "Clicker activation key or mouse button, Escape clears it",
+        );
+        for (button, tooltip) in [
+            (&self.start_key_button, "Key that starts the clicker"),
+            (&self.stop_key_button, "Key that stops the clicker"),
+            (
+                &self.pause_key_button,
+                "Key that pauses and resumes the clicker",
+            ),
+            (&self.panic_key_button, "Key that always stops the clicker"),
+        ] {
+            self.tooltip
+                .register(button, &format!("{}, Escape clears it", tooltip));
+        }
+        for action in HotkeyAction::iter() {
+            self.tooltip.register(
+                self.consume_check_box(action),
+                "Hide the key from the other applications",
+            );
+        }
+        self.update_consume_check_boxes(&self.settings.lock().unwrap());
+        if self.settings.lock().unwrap().physical_keys_only {
+            self.physical_keys_only_check_box
+                .set_check_state(CheckBoxState::Checked);
+        }
+        self.tooltip.register(
+            &self.physical_keys_only_check_box,
+            "Ignore hotkeys that other programs inject",
+        );
+        for target_order in TargetOrder::iter() {
+            self.target_order_selector.push(target_order);
+            if target_order == self.settings.lock().unwrap().target_order {
+                self.target_order_selector
+                    .set_selection(Some(self.target_order_selector.len() - 1));
+            }
+        }
+        for delivery in Delivery::iter() {
+            self.delivery_selector.push(delivery);
+            if delivery == self.settings.lock().unwrap().delivery {
+                self.delivery_selector
+                    .set_selection(Some(self.delivery_selector.len() - 1));
+            }
+        }
+        self.update_delivery_selector();
+        if self.settings.lock().unwrap().restore_cursor {
+            self.restore_cursor_check_box
+                .set_check_state(CheckBoxState::Checked);
+        }
+        self.tooltip.register(
+            &self.targets_selector,
+            "Points to click at: 100,200; 300,400*3 clicks the second point 3 times, empty for the cursor position",
+        );
         self.tooltip
-            .register(&self.click_duration_selector, "Duration of click, ms");
-        self.tooltip
-            .register(&self.click_activator_button, "Clicker activation key");
+            .register(&self.target_order_selector, "Order of the points");
+        self.tooltip.register(
+            &self.target_window_selector,
+            "Window the points are relative to, e.g. process:game.exe; title:Level 1, empty for the screen",
+        );
+        self.tooltip.register(
+            &self.focus_window_selector,
+            "Window that must be focused to click, e.g. process:game.exe, empty for any window",
+        );
+        self.tooltip.register(
+            &self.delivery_selector,
+            "Post to window sends the clicks to the target window without moving the pointer",
+        );
+        self.tooltip.register(
+            &self.restore_cursor_check_box,
+            "Move the pointer back when the clicker stops",
+        );
+        self.tooltip.register(
+            &self.activation_mode_selector,
+            "Toggle on each press, click while held or one burst per press",
+        );
+        self.tooltip.register(
+            &self.click_limit_selector,
+            "Stop after this many clicks, empty for no limit",
+        );
+        self.tooltip.register(
+            &self.time_limit_selector,
+            "Stop after this many seconds, empty for no limit",
+        );
 
-        let mut keyboard_hook = KeyboardHook::new(Arc::new({
+        let callback: Callback = Arc::new({
             let settings = self.settings.clone();
-            let is_changing_activator_key = self.is_changing_activator_key.clone();
-            let sender = self.click_activator_update_text_notice.sender();
-
-            move |vk_code| {
-                let mut lock = is_changing_activator_key.lock().unwrap();
-                if *lock {
-                    if let Some(key) = FromPrimitive::from_u32(vk_code) {
-                        settings.lock().unwrap().activation_key = Some(key);
-                        *lock = false;
+            let changing_hotkey = self.changing_hotkey.clone();
+            let sender = self.hotkey_update_text_notice.sender();
+            let pending_modifier = Mutex::new(None);
+
+            move |event: KeyEvent| {
+                // The buttons that operate the window cannot be bound.
+                if event.key == Keys::LButton.into() || event.key == Keys::RButton.into() {
+                    return;
+                }
+                let mut lock = changing_hotkey.lock().unwrap();
+                if let Some(action) = *lock {
+                    let mut pending_modifier = pending_modifier.lock().unwrap();
+                    // A modifier only becomes the key of the hotkey when it is
+                    // released without pressing another key.
+                    let is_modifier = Modifier::of_key(event.key).is_some();
+                    let hotkey = match (event.is_pressed, is_modifier) {
+                        (true, false) if event.key == Keys::Escape.into() => Some(None),
+                        (true, true) => {
+                            *pending_modifier = Some(event.key);
+                            None
+                        }
+                        (true, false) => Some(Some(Hotkey::new(event.key, event.modifiers))),
+                        (false, _) if *pending_modifier == Some(event.key) => {
+                            Some(Some(Hotkey::new(event.key, event.modifiers)))
+                        }
+                        (false, _) => None,
+                    };
+                    if let Some(hotkey) = hotkey {
+                        *pending_modifier = None;
+                        let mut settings = settings.lock().unwrap();
+                        let binding = settings.binding_mut(action);
+                        // A new hotkey keeps the options of the previous one.
+                        *binding = hotkey.map(|hotkey| Binding {
+                            hotkey,
+                            ..binding.unwrap_or_else(|| hotkey.into())
+                        });
+                        *lock = None;
                         sender.notice();
                     }
                 }
             }
-        }));
+        });
+        let mut keyboard_hook = KeyboardHook::new(callback.clone()).unwrap();
         keyboard_hook.start();
         *self.keyboard_hook.borrow_mut() = Some(keyboard_hook);
+        let mut mouse_hook = MouseHook::new(callback).unwrap();
+        mouse_hook.start();
+        *self.mouse_hook.borrow_mut() = Some(mouse_hook);
     }
 
     fn on_close(&self) {
@@ -151,35 +424,246 @@ impl SettingsEditor {
             self.click_type_selector.collection()[self.click_type_selector.selection().unwrap()];
     }
 
+    fn on_wheel_delta_changed(&self) {
+        if let Ok(wheel_delta) = self.wheel_delta_selector.text().parse::<i32>() {
+            if wheel_delta != 0 && (-MAX_WHEEL_DELTA..=MAX_WHEEL_DELTA).contains(&wheel_delta) {
+                self.settings.lock().unwrap().wheel_delta = wheel_delta;
+            }
+        }
+    }
+
+    fn on_activation_mode_selected(&self) {
+        self.settings.lock().unwrap().activation_mode = self.activation_mode_selector.collection()
+            [self.activation_mode_selector.selection().unwrap()];
+    }
+
     fn on_click_delay_changed(&self) {
-        if let Ok(new_delay) = self.click_delay_selector.text().parse::<u64>() {
-            self.settings.lock().unwrap().click_delay = Duration::from_millis(new_delay);
+        if self.is_syncing_rate.get() {
+            return;
+        }
+        if let Ok(new_delay) = self.click_delay_selector.text().parse::<Interval>() {
+            let new_rate = Rate::Period(new_delay);
+            self.settings.lock().unwrap().click_rate = new_rate;
+            self.sync_rate(
+                &self.clicks_per_second_selector,
+                &format_clicks_per_second(new_rate.clicks_per_second()),
+            );
+        }
+    }
+
+    fn on_clicks_per_second_changed(&self) {
+        if self.is_syncing_rate.get() {
+            return;
+        }
+        if let Ok(new_clicks_per_second) = self.clicks_per_second_selector.text().parse::<f64>() {
+            if new_clicks_per_second.is_finite() && new_clicks_per_second > 0.0 {
+                let new_rate = Rate::ClicksPerSecond(new_clicks_per_second);
+                self.settings.lock().unwrap().click_rate = new_rate;
+                self.sync_rate(&self.click_delay_selector, &new_rate.period().to_string());
+            }
         }
     }
 
+    /// Shows the rate that was entered in one view in the other view.
+    fn sync_rate(&self, view: &TextInput, text: &str) {
+        self.is_syncing_rate.set(true);
+        view.set_text(text);
+        self.is_syncing_rate.set(false);
+    }
+
     fn on_click_duration_changed(&self) {
-        if let Ok(new_duration) = self.click_duration_selector.text().parse::<u64>() {
-            self.settings.lock().unwrap().click_duration = Duration::from_millis(new_duration);
+        if let Ok(new_duration) = self.click_duration_selector.text().parse() {
+            self.settings.lock().unwrap().click_duration = new_duration;
+        }
+    }
+
+    fn on_click_limit_changed(&self) {
+        let text = self.click_limit_selector.text();
+        if text.is_empty() {
+            self.settings.lock().unwrap().click_limit = None;
+        } else if let Ok(new_click_limit) = text.parse::<u64>() {
+            self.settings.lock().unwrap().click_limit = Some(new_click_limit);
+        }
+    }
+
+    fn on_time_limit_changed(&self) {
+        let text = self.time_limit_selector.text();
+        if text.is_empty() {
+            self.settings.lock().unwrap().time_limit = None;
+        } else if let Some(new_time_limit) = text
+            .parse::<f64>()
+            .ok()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        {
+            self.settings.lock().unwrap().time_limit = Some(new_time_limit);
         }
     }
 
     fn on_click_activator_click(&self) {
-        if let Ok(mut lock) = self.is_changing_activator_key.try_lock() {
-            if !*lock {
-                self.click_activator_button.set_text("Press key...");
-                *lock = true;
+        self.change_hotkey(HotkeyAction::Activate);
+    }
+
+    fn on_start_key_click(&self) {
+        self.change_hotkey(HotkeyAction::Start);
+    }
+
+    fn on_stop_key_click(&self) {
+        self.change_hotkey(HotkeyAction::Stop);
+    }
+
+    fn on_pause_key_click(&self) {
+        self.change_hotkey(HotkeyAction::Pause);
+    }
+
+    fn on_panic_key_click(&self) {
+        self.change_hotkey(HotkeyAction::Panic);
+    }
+
+    /// Captures the next hotkey pressed for `action`.
+    fn change_hotkey(&self, action: HotkeyAction) {
+        if let Ok(mut lock) = self.changing_hotkey.try_lock() {
+            if lock.is_none() {
+                self.hotkey_button(action).set_text("Press key...");
+                *lock = Some(action);
+            }
+        }
+    }
+
+    fn hotkey_button(&self, action: HotkeyAction) -> &Button {
+        match action {
+            HotkeyAction::Activate => &self.click_activator_button,
+            HotkeyAction::Start => &self.start_key_button,
+            HotkeyAction::Stop => &self.stop_key_button,
+            HotkeyAction::Pause => &self.pause_key_button,
+            HotkeyAction::Panic => &self.panic_key_button,
+        }
+    }
+
+    fn consume_check_box(&self, action: HotkeyAction) -> &CheckBox {
+        match action {
+            HotkeyAction::Activate => &self.click_activator_consume_check_box,
+            HotkeyAction::Start => &self.start_key_consume_check_box,
+            HotkeyAction::Stop => &self.stop_key_consume_check_box,
+            HotkeyAction::Pause => &self.pause_key_consume_check_box,
+            HotkeyAction::Panic => &self.panic_key_consume_check_box,
+        }
+    }
+
+    /// Shows the consume option of each binding, it can only be changed once
+    /// a hotkey is set.
+    fn update_consume_check_boxes(&self, settings: &Settings) {
+        for action in HotkeyAction::iter() {
+            let binding = settings.binding(action);
+            let check_box = self.consume_check_box(action);
+            check_box.set_check_state(if binding.is_some_and(|binding| binding.consume) {
+                CheckBoxState::Checked
+            } else {
+                CheckBoxState::Unchecked
+            });
+            check_box.set_enabled(binding.is_some());
+        }
+    }
+
+    fn on_consume_click(&self) {
+        let mut settings = self.settings.lock().unwrap();
+        for action in HotkeyAction::iter() {
+            let is_checked = self.consume_check_box(action).check_state() == CheckBoxState::Checked;
+            if let Some(binding) = settings.binding_mut(action) {
+                binding.consume = is_checked;
             }
         }
     }
 
-    fn on_click_activator_update_text_notice(&self) {
-        let lock = self.is_changing_activator_key.lock().unwrap();
-        if !*lock {
-            let lock = self.settings.lock().unwrap();
-            self.click_activator_button
-                .set_text(&lock.activation_key.unwrap().to_string());
-        } else {
-            self.click_activator_button.set_text("Press key...");
+    fn on_targets_changed(&self) {
+        if let Ok(targets) = parse_targets(&self.targets_selector.text()) {
+            self.settings.lock().unwrap().targets = targets;
         }
     }
+
+    fn on_target_order_selected(&self) {
+        self.settings.lock().unwrap().target_order = self.target_order_selector.collection()
+            [self.target_order_selector.selection().unwrap()];
+    }
+
+    fn on_target_window_changed(&self) {
+        let text = self.target_window_selector.text();
+        if text.trim().is_empty() {
+            self.settings.lock().unwrap().target_window = None;
+        } else if let Ok(target_window) = text.parse() {
+            self.settings.lock().unwrap().target_window = Some(target_window);
+        }
+        self.update_delivery_selector();
+    }
+
+    /// Clicks can only be posted to the target window, without one the
+    /// delivery is global and cannot be changed.
+    fn update_delivery_selector(&self) {
+        let mut settings = self.settings.lock().unwrap();
+        let has_target_window = settings.target_window.is_some();
+        if !has_target_window && settings.delivery != Delivery::Global {
+            settings.delivery = Delivery::Global;
+            self.delivery_selector
+                .set_selection(Delivery::iter().position(|delivery| delivery == Delivery::Global));
+        }
+        self.delivery_selector.set_enabled(has_target_window);
+    }
+
+    fn on_focus_window_changed(&self) {
+        let text = self.focus_window_selector.text();
+        if text.trim().is_empty() {
+            self.settings.lock().unwrap().focus_window = None;
+        } else if let Ok(focus_window) = text.parse() {
+            self.settings.lock().unwrap().focus_window = Some(focus_window);
+        }
+    }
+
+    fn on_delivery_selected(&self) {
+        self.settings.lock().unwrap().delivery =
+            self.delivery_selector.collection()[self.delivery_selector.selection().unwrap()];
+    }
+
+    fn on_restore_cursor_click(&self) {
+        self.settings.lock().unwrap().restore_cursor =
+            self.restore_cursor_check_box.check_state() == CheckBoxState::Checked;
+    }
+
+    fn on_physical_keys_only_click(&self) {
+        self.settings.lock().unwrap().physical_keys_only =
+            self.physical_keys_only_check_box.check_state() == CheckBoxState::Checked;
+    }
+
+    fn on_hotkey_update_text_notice(&self) {
+        let lock = self.changing_hotkey.lock().unwrap();
+        let settings = self.settings.lock().unwrap();
+        for action in HotkeyAction::iter() {
+            if *lock == Some(action) {
+                self.hotkey_button(action).set_text("Press key...");
+            } else {
+                self.hotkey_button(action)
+                    .set_text(&hotkey_text(&settings, action));
+            }
+        }
+        self.update_consume_check_boxes(&settings);
+    }
+}
+
+fn hotkey_text(settings: &Settings, action: HotkeyAction) -> String {
+    match (
+        action,
+        settings.binding(action).map(|binding| binding.hotkey),
+    ) {
+        (HotkeyAction::Activate, Some(hotkey)) => hotkey.to_string(),
+        (HotkeyAction::Activate, None) => "No key selected".to_string(),
+        (action, Some(hotkey)) => format!("{} key: {}", action, hotkey),
+        (action, None) => format!("No {} key", action.to_string().to_lowercase()),
+    }
+}
+
+fn format_clicks_per_second(clicks_per_second: f64) -> String {
+    let text = format!("{:.3}", clicks_per_second);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
 }
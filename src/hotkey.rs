@@ -0,0 +1,365 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Hotkeys and the bindings of the clicker operations to them.
+
+use crate::keyboard_hook::KeyEvent;
+use crate::keys::{Key, Keys};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Modifier key of a hotkey, regardless of its side.
+#[derive(PartialEq, Eq, Hash, EnumIter, Copy, Clone, Debug)]
+pub enum Modifier {
+    Control,
+    Shift,
+    Alt,
+    Win,
+}
+
+/// Which of the two keys of a modifier is meant.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Side {
+    /// Either key, or both.
+    Either,
+    Left,
+    Right,
+}
+
+impl Modifier {
+    /// Modifier and side of `key`, the generic modifier keys count as left.
+    pub fn of_key(key: Key) -> Option<(Self, Side)> {
+        match key {
+            Key::Named(key) => Some(match key {
+                Keys::ControlKey | Keys::LControlKey => (Modifier::Control, Side::Left),
+                Keys::RControlKey => (Modifier::Control, Side::Right),
+                Keys::ShiftKey | Keys::LShiftKey => (Modifier::Shift, Side::Left),
+                Keys::RShiftKey => (Modifier::Shift, Side::Right),
+                Keys::Menu | Keys::LMenu => (Modifier::Alt, Side::Left),
+                Keys::RMenu => (Modifier::Alt, Side::Right),
+                Keys::LWin => (Modifier::Win, Side::Left),
+                Keys::RWin => (Modifier::Win, Side::Right),
+                _ => return None,
+            }),
+            Key::Unnamed(_) => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Modifier::Control => "Ctrl",
+            Modifier::Shift => "Shift",
+            Modifier::Alt => "Alt",
+            Modifier::Win => "Win",
+        }
+    }
+}
+
+/// Set of modifier keys that are held down.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Default)]
+pub struct ModifierKeys(u8);
+
+impl ModifierKeys {
+    fn mask(modifier: Modifier, side: Side) -> u8 {
+        let left = 1 << (modifier as u8 * 2);
+        match side {
+            Side::Either => left | left << 1,
+            Side::Left => left,
+            Side::Right => left << 1,
+        }
+    }
+
+    /// Whether the modifier is held down on `side`, on any side for
+    /// `Side::Either`.
+    pub fn is_pressed(self, modifier: Modifier, side: Side) -> bool {
+        self.0 & Self::mask(modifier, side) != 0
+    }
+
+    /// Records a press or release of one of the keys of `modifier`.
+    pub fn set(&mut self, modifier: Modifier, side: Side, is_pressed: bool) {
+        if is_pressed {
+            self.0 |= Self::mask(modifier, side);
+        } else {
+            self.0 &= !Self::mask(modifier, side);
+        }
+    }
+}
+
+/// Key combined with the modifiers that must be held down with it, such as
+/// `Ctrl+Alt+F6`. Modifiers that are not required must not be held down.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct Hotkey {
+    /// Key that triggers the hotkey, the modifiers only have to be held.
+    pub key: Key,
+    pub control: Option<Side>,
+    pub shift: Option<Side>,
+    pub alt: Option<Side>,
+    pub win: Option<Side>,
+}
+
+impl From<Key> for Hotkey {
+    fn from(key: Key) -> Self {
+        Self {
+            key,
+            control: None,
+            shift: None,
+            alt: None,
+            win: None,
+        }
+    }
+}
+
+impl From<Keys> for Hotkey {
+    fn from(key: Keys) -> Self {
+        Key::from(key).into()
+    }
+}
+
+impl Hotkey {
+    /// Hotkey of `key` that requires the held down `modifiers` on either side.
+    pub fn new(key: Key, modifiers: ModifierKeys) -> Self {
+        let mut hotkey = Self::from(key);
+        for modifier in Modifier::iter() {
+            if modifiers.is_pressed(modifier, Side::Either) {
+                *hotkey.modifier_mut(modifier) = Some(Side::Either);
+            }
+        }
+        hotkey
+    }
+
+    /// Side of `modifier` that must be held down, `None` when it must not.
+    pub fn modifier(&self, modifier: Modifier) -> Option<Side> {
+        match modifier {
+            Modifier::Control => self.control,
+            Modifier::Shift => self.shift,
+            Modifier::Alt => self.alt,
+            Modifier::Win => self.win,
+        }
+    }
+
+    /// Requirement for `modifier`, see `modifier`.
+    pub fn modifier_mut(&mut self, modifier: Modifier) -> &mut Option<Side> {
+        match modifier {
+            Modifier::Control => &mut self.control,
+            Modifier::Shift => &mut self.shift,
+            Modifier::Alt => &mut self.alt,
+            Modifier::Win => &mut self.win,
+        }
+    }
+
+    /// Whether `event` is a press of this hotkey, or a release of its key
+    /// whatever modifiers are still held down.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        event.key == self.key
+            && (!event.is_pressed
+                || Modifier::iter().all(|modifier| match self.modifier(modifier) {
+                    Some(side) => event.modifiers.is_pressed(modifier, side),
+                    None => !event.modifiers.is_pressed(modifier, Side::Either),
+                }))
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in Modifier::iter() {
+            let prefix = match self.modifier(modifier) {
+                Some(Side::Either) => "",
+                Some(Side::Left) => "L",
+                Some(Side::Right) => "R",
+                None => continue,
+            };
+            write!(f, "{}{}+", prefix, modifier.name())?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    /// Parses the `Display` form, modifiers ignore case and take an `L` or
+    /// `R` prefix to require one side, e.g. `LCtrl+Shift+F6`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let mut hotkey = Self::from(parts.pop().unwrap().parse::<Key>()?);
+        for part in parts {
+            let find = |name: &str| {
+                Modifier::iter().find(|modifier| modifier.name().eq_ignore_ascii_case(name))
+            };
+            let (modifier, side) = match find(part) {
+                Some(modifier) => (modifier, Side::Either),
+                None => match (part.get(..1), part.get(1..).and_then(find)) {
+                    (Some("L" | "l"), Some(modifier)) => (modifier, Side::Left),
+                    (Some("R" | "r"), Some(modifier)) => (modifier, Side::Right),
+                    _ => return Err(format!("unknown modifier '{}'", part)),
+                },
+            };
+            *hotkey.modifier_mut(modifier) = Some(side);
+        }
+        Ok(hotkey)
+    }
+}
+
+impl Serialize for Hotkey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hotkey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Hotkey bound to an operation of the clicker, with the options of how the
+/// keyboard hook treats it.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(from = "BindingRepr", into = "BindingRepr")]
+pub struct Binding {
+    /// Keys that trigger the operation.
+    pub hotkey: Hotkey,
+    /// Hide the key events of the hotkey from the other applications.
+    pub consume: bool,
+    /// Ignore the auto-repeats of the hotkey while it is held down.
+    pub ignore_repeat: bool,
+}
+
+impl From<Hotkey> for Binding {
+    fn from(hotkey: Hotkey) -> Self {
+        Self {
+            hotkey,
+            consume: false,
+            ignore_repeat: true,
+        }
+    }
+}
+
+impl Binding {
+    /// Whether `event` matches the hotkey and is not an ignored repeat.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        !(event.is_repeat && self.ignore_repeat) && self.hotkey.matches(event)
+    }
+}
+
+/// Stored form of `Binding`, a binding without options is only its hotkey as
+/// written by older versions.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum BindingRepr {
+    Hotkey(Hotkey),
+    Binding {
+        hotkey: Hotkey,
+        #[serde(default)]
+        consume: bool,
+        #[serde(default = "default_ignore_repeat")]
+        ignore_repeat: bool,
+    },
+}
+
+fn default_ignore_repeat() -> bool {
+    true
+}
+
+impl From<BindingRepr> for Binding {
+    fn from(repr: BindingRepr) -> Self {
+        match repr {
+            BindingRepr::Hotkey(hotkey) => hotkey.into(),
+            BindingRepr::Binding {
+                hotkey,
+                consume,
+                ignore_repeat,
+            } => Self {
+                hotkey,
+                consume,
+                ignore_repeat,
+            },
+        }
+    }
+}
+
+impl From<Binding> for BindingRepr {
+    fn from(binding: Binding) -> Self {
+        if binding == Binding::from(binding.hotkey) {
+            BindingRepr::Hotkey(binding.hotkey)
+        } else {
+            BindingRepr::Binding {
+                hotkey: binding.hotkey,
+                consume: binding.consume,
+                ignore_repeat: binding.ignore_repeat,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modifiers_and_sides() {
+        let hotkey: Hotkey = "LCtrl+shift+F6".parse().unwrap();
+        assert_eq!(hotkey.key, Key::Named(Keys::F6));
+        assert_eq!(hotkey.control, Some(Side::Left));
+        assert_eq!(hotkey.shift, Some(Side::Either));
+        assert_eq!(hotkey.alt, None);
+        assert_eq!(hotkey.win, None);
+        assert!("Hyper+F6".parse::<Hotkey>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in ["F6", "Ctrl+Alt+F6", "RShift+LWin+A"] {
+            let hotkey: Hotkey = text.parse().unwrap();
+            assert_eq!(hotkey.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn matches_only_the_required_modifiers() {
+        let hotkey: Hotkey = "LCtrl+F6".parse().unwrap();
+        let mut modifiers = ModifierKeys::default();
+        let event = |modifiers| KeyEvent {
+            key: Keys::F6.into(),
+            is_pressed: true,
+            is_repeat: false,
+            is_injected: false,
+            modifiers,
+        };
+        assert!(!hotkey.matches(&event(modifiers)));
+        modifiers.set(Modifier::Control, Side::Right, true);
+        assert!(!hotkey.matches(&event(modifiers)));
+        modifiers.set(Modifier::Control, Side::Left, true);
+        assert!(hotkey.matches(&event(modifiers)));
+        modifiers.set(Modifier::Shift, Side::Left, true);
+        assert!(!hotkey.matches(&event(modifiers)));
+    }
+
+    #[test]
+    fn bindings_without_options_are_stored_as_their_hotkey() {
+        let binding = Binding::from(Hotkey::from(Keys::F6));
+        assert_eq!(serde_json::to_string(&binding).unwrap(), r#""F6""#);
+        assert_eq!(serde_json::from_str(r#""F6""#).ok(), Some(binding));
+        let consumed: Binding = serde_json::from_str(r#"{"hotkey":"F6","consume":true}"#).unwrap();
+        assert!(consumed.consume && consumed.ignore_repeat);
+    }
+}
@@ -0,0 +1,77 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{InputBackend, InputEvent, MouseButton, ScrollAxis};
+use crate::keys::Key;
+use std::io::{self, Write};
+use std::time::Instant;
+
+/// Backend that injects nothing and prints every event to stdout instead,
+/// prefixed with the seconds elapsed since its creation.
+pub struct LoggingBackend {
+    created: Instant,
+}
+
+impl Default for LoggingBackend {
+    fn default() -> Self {
+        Self {
+            created: Instant::now(),
+        }
+    }
+}
+
+impl LoggingBackend {
+    /// Creates a backend that counts time from now.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn log(&self, event: InputEvent) -> io::Result<()> {
+        writeln!(
+            io::stdout(),
+            "{:>10.3} {:?}",
+            self.created.elapsed().as_secs_f64(),
+            event
+        )
+    }
+}
+
+impl InputBackend for LoggingBackend {
+    fn press(&self, button: MouseButton) -> io::Result<()> {
+        self.log(InputEvent::Press(button))
+    }
+
+    fn release(&self, button: MouseButton) -> io::Result<()> {
+        self.log(InputEvent::Release(button))
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> io::Result<()> {
+        self.log(InputEvent::MoveTo { x, y })
+    }
+
+    fn scroll(&self, axis: ScrollAxis, delta: i32) -> io::Result<()> {
+        self.log(InputEvent::Scroll { axis, delta })
+    }
+
+    fn key_down(&self, key: Key) -> io::Result<()> {
+        self.log(InputEvent::KeyDown(key))
+    }
+
+    fn key_up(&self, key: Key) -> io::Result<()> {
+        self.log(InputEvent::KeyUp(key))
+    }
+}
@@ -0,0 +1,149 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Backends that inject the synthetic input of the clicker.
+
+mod logging;
+mod recording;
+#[cfg(all(target_os = "linux", feature = "backend-uinput"))]
+pub(crate) mod uinput;
+#[cfg(all(windows, feature = "backend-win32"))]
+mod win32;
+mod window;
+#[cfg(all(target_os = "linux", feature = "backend-x11"))]
+mod xtest;
+
+pub use logging::LoggingBackend;
+pub use recording::{InputEvent, RecordedEvent, RecordingBackend};
+#[cfg(all(target_os = "linux", feature = "backend-uinput"))]
+pub use uinput::UinputBackend;
+#[cfg(all(windows, feature = "backend-win32"))]
+pub use win32::Win32Backend;
+pub use window::WindowBackend;
+#[cfg(all(target_os = "linux", feature = "backend-x11"))]
+pub use xtest::XTestBackend;
+
+use crate::keys::{Key, Keys};
+#[cfg(all(target_os = "linux", feature = "backend-x11"))]
+use std::env;
+use std::io;
+use std::time::Duration;
+use strum::EnumIter;
+
+/// Mouse button that a backend presses and releases.
+#[derive(PartialEq, Eq, EnumIter, Copy, Clone, Debug)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// First side button, usually "back".
+    X1,
+    /// Second side button, usually "forward".
+    X2,
+}
+
+impl From<MouseButton> for Keys {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => Keys::LButton,
+            MouseButton::Right => Keys::RButton,
+            MouseButton::Middle => Keys::MButton,
+            MouseButton::X1 => Keys::XButton1,
+            MouseButton::X2 => Keys::XButton2,
+        }
+    }
+}
+
+/// Wheel that a backend scrolls.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ScrollAxis {
+    Vertical,
+    Horizontal,
+}
+
+/// Sink for synthetic input events emitted by the clicker.
+pub trait InputBackend: Send + Sync {
+    /// Presses `button` down where the pointer is.
+    fn press(&self, button: MouseButton) -> io::Result<()>;
+
+    /// Releases `button` where the pointer is.
+    fn release(&self, button: MouseButton) -> io::Result<()>;
+
+    /// Moves the pointer to absolute screen coordinates.
+    fn move_to(&self, x: i32, y: i32) -> io::Result<()>;
+
+    /// Absolute screen coordinates of the pointer, not every backend can
+    /// read them.
+    fn cursor_position(&self) -> io::Result<(i32, i32)> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the backend cannot read the cursor position",
+        ))
+    }
+
+    /// Scrolls by `delta` wheel ticks, positive values scroll up or right.
+    fn scroll(&self, axis: ScrollAxis, delta: i32) -> io::Result<()>;
+
+    /// Presses `key` down, fails for keys without a code on the platform.
+    fn key_down(&self, key: Key) -> io::Result<()>;
+
+    /// Releases `key`, see `key_down`.
+    fn key_up(&self, key: Key) -> io::Result<()>;
+}
+
+/// Longest time between the clicks of a double click. Only Windows has a
+/// system-wide setting, elsewhere this is the default of GTK and Qt.
+#[allow(unreachable_code)]
+pub fn double_click_interval() -> Duration {
+    #[cfg(all(windows, feature = "backend-win32"))]
+    return Duration::from_millis(unsafe { winapi::um::winuser::GetDoubleClickTime() }.into());
+
+    Duration::from_millis(400)
+}
+
+/// Creates the native backend of the current platform: Win32 on Windows,
+/// XTest in an X11 session and uinput everywhere else on Linux. The uinput
+/// pointer cannot `move_to`, so it is refused when `needs_absolute_motion`
+/// is set, such as for clicking at targets.
+#[allow(unreachable_code, unused_variables)]
+pub fn default_backend(needs_absolute_motion: bool) -> io::Result<Box<dyn InputBackend>> {
+    #[cfg(all(windows, feature = "backend-win32"))]
+    return Ok(Box::new(Win32Backend));
+
+    #[cfg(all(target_os = "linux", feature = "backend-x11"))]
+    if env::var_os("DISPLAY").is_some() && env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Ok(Box::new(XTestBackend::new()?));
+    }
+
+    #[cfg(all(target_os = "linux", feature = "backend-uinput"))]
+    return if needs_absolute_motion {
+        // The size of the screen, which the axes of an absolute pointer span,
+        // is only known to X11.
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "clicking at points needs an X11 session, the uinput pointer used \
+             outside of it can only move relatively",
+        ))
+    } else {
+        Ok(Box::new(UinputBackend::new()?))
+    };
+
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no input backend for this platform was enabled at build time",
+    ))
+}
@@ -0,0 +1,116 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{InputBackend, MouseButton, ScrollAxis};
+use crate::keys::Key;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Call of an `InputBackend` method, with its arguments.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum InputEvent {
+    Press(MouseButton),
+    Release(MouseButton),
+    MoveTo { x: i32, y: i32 },
+    Scroll { axis: ScrollAxis, delta: i32 },
+    KeyDown(Key),
+    KeyUp(Key),
+}
+
+/// Event kept by a `RecordingBackend`.
+#[derive(Copy, Clone, Debug)]
+pub struct RecordedEvent {
+    pub event: InputEvent,
+    /// When the backend received the event.
+    pub timestamp: Instant,
+}
+
+/// Backend that injects nothing and keeps every event in memory instead.
+///
+/// Clones share the same buffer, so a clone can be handed to a `Clicker`
+/// while the original is used to inspect what was emitted.
+#[derive(Clone, Default)]
+pub struct RecordingBackend {
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+}
+
+impl RecordingBackend {
+    /// Creates a backend with an empty buffer.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Events received so far, in order, by this backend and its clones.
+    pub fn events(&self) -> Vec<RecordedEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Forgets the events received so far.
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    fn record(&self, event: InputEvent) -> io::Result<()> {
+        self.events.lock().unwrap().push(RecordedEvent {
+            event,
+            timestamp: Instant::now(),
+        });
+        Ok(())
+    }
+}
+
+impl InputBackend for RecordingBackend {
+    fn press(&self, button: MouseButton) -> io::Result<()> {
+        self.record(InputEvent::Press(button))
+    }
+
+    fn release(&self, button: MouseButton) -> io::Result<()> {
+        self.record(InputEvent::Release(button))
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> io::Result<()> {
+        self.record(InputEvent::MoveTo { x, y })
+    }
+
+    /// The position of the last `move_to`, the origin before any.
+    fn cursor_position(&self) -> io::Result<(i32, i32)> {
+        Ok(self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find_map(|recorded| match recorded.event {
+                InputEvent::MoveTo { x, y } => Some((x, y)),
+                _ => None,
+            })
+            .unwrap_or((0, 0)))
+    }
+
+    fn scroll(&self, axis: ScrollAxis, delta: i32) -> io::Result<()> {
+        self.record(InputEvent::Scroll { axis, delta })
+    }
+
+    fn key_down(&self, key: Key) -> io::Result<()> {
+        self.record(InputEvent::KeyDown(key))
+    }
+
+    fn key_up(&self, key: Key) -> io::Result<()> {
+        self.record(InputEvent::KeyUp(key))
+    }
+}
@@ -0,0 +1,302 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{InputBackend, MouseButton, ScrollAxis};
+use crate::evdev::{self, *};
+use crate::keys::{Key, Keys};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+
+const UINPUT_PATH: &str = "/dev/uinput";
+/// Name of the pointers created for injection.
+pub(crate) const POINTER_DEVICE_NAME: &str = "rsautoclicker virtual pointer";
+/// Name of the device that re-emits the key events of grabbed keyboards.
+pub(crate) const KEYBOARD_DEVICE_NAME: &str = "rsautoclicker virtual keyboard";
+
+/// Injects input through a virtual device created with `/dev/uinput`.
+pub struct UinputBackend {
+    device: File,
+    has_absolute_axes: bool,
+}
+
+impl Drop for UinputBackend {
+    fn drop(&mut self) {
+        let _ = evdev::ioctl(&self.device, UI_DEV_DESTROY, 0);
+    }
+}
+
+impl UinputBackend {
+    /// Creates a relative pointer, `move_to` is not supported by it.
+    pub fn new() -> io::Result<Self> {
+        Self::create(POINTER_DEVICE_NAME, None, Self::pointer_keys())
+    }
+
+    /// Creates an absolute pointer whose axes span a screen of the given size.
+    pub fn with_screen_size(width: i32, height: i32) -> io::Result<Self> {
+        Self::create(
+            POINTER_DEVICE_NAME,
+            Some((width, height)),
+            Self::pointer_keys(),
+        )
+    }
+
+    /// Creates the device through which the keyboard hook passes on the key
+    /// events that it does not consume. It declares every key code, so that
+    /// keys without a name in `Keys` are passed on too.
+    pub(crate) fn keyboard() -> io::Result<Self> {
+        Self::create(KEYBOARD_DEVICE_NAME, None, 1..=KEY_MAX)
+    }
+
+    /// Creates a keyboard that the listeners take for one of another program.
+    #[cfg(test)]
+    pub(crate) fn keyboard_named(name: &str) -> io::Result<Self> {
+        Self::create(name, None, 1..=KEY_MAX)
+    }
+
+    /// Buttons and keys that a pointer can inject.
+    fn pointer_keys() -> impl Iterator<Item = u16> {
+        [BTN_LEFT, BTN_RIGHT, BTN_MIDDLE, BTN_SIDE, BTN_EXTRA]
+            .iter()
+            .copied()
+            .chain(Keys::evdev_codes())
+    }
+
+    fn create(
+        name: &str,
+        screen_size: Option<(i32, i32)>,
+        key_codes: impl Iterator<Item = u16>,
+    ) -> io::Result<Self> {
+        let device = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(UINPUT_PATH)
+            .map_err(|error| {
+                io::Error::new(
+                    error.kind(),
+                    format!("{} is not writable: {}", UINPUT_PATH, error),
+                )
+            })?;
+
+        evdev::ioctl(&device, UI_SET_EVBIT, EV_KEY.into())?;
+        for code in key_codes {
+            evdev::ioctl(&device, UI_SET_KEYBIT, code.into())?;
+        }
+
+        evdev::ioctl(&device, UI_SET_EVBIT, EV_REL.into())?;
+        for code in &[REL_WHEEL, REL_HWHEEL] {
+            evdev::ioctl(&device, UI_SET_RELBIT, (*code).into())?;
+        }
+
+        match screen_size {
+            Some((width, height)) => {
+                evdev::ioctl(&device, UI_SET_EVBIT, EV_ABS.into())?;
+                for (code, size) in &[(ABS_X, width), (ABS_Y, height)] {
+                    evdev::ioctl(&device, UI_SET_ABSBIT, (*code).into())?;
+                    let mut abs_setup: libc::uinput_abs_setup = unsafe { mem::zeroed() };
+                    abs_setup.code = *code;
+                    abs_setup.absinfo.maximum = size - 1;
+                    evdev::ioctl(
+                        &device,
+                        UI_ABS_SETUP,
+                        &abs_setup as *const _ as libc::c_ulong,
+                    )?;
+                }
+            }
+            None => {
+                for code in &[REL_X, REL_Y] {
+                    evdev::ioctl(&device, UI_SET_RELBIT, (*code).into())?;
+                }
+            }
+        }
+
+        let mut setup: libc::uinput_setup = unsafe { mem::zeroed() };
+        setup.id.bustype = BUS_VIRTUAL;
+        for (destination, source) in setup.name.iter_mut().zip(name.bytes()) {
+            *destination = source as libc::c_char;
+        }
+        evdev::ioctl(&device, UI_DEV_SETUP, &setup as *const _ as libc::c_ulong)?;
+        evdev::ioctl(&device, UI_DEV_CREATE, 0)?;
+
+        Ok(Self {
+            device,
+            has_absolute_axes: screen_size.is_some(),
+        })
+    }
+
+    /// Emits an event read from another device as it is.
+    pub(crate) fn forward(&self, event: &libc::input_event) -> io::Result<()> {
+        evdev::write_event(&self.device, event.type_, event.code, event.value)
+    }
+
+    fn emit(&self, kind: u16, code: u16, value: i32) -> io::Result<()> {
+        evdev::write_event(&self.device, kind, code, value)?;
+        evdev::write_report(&self.device)
+    }
+
+    fn button_code(button: MouseButton) -> u16 {
+        match button {
+            MouseButton::Left => BTN_LEFT,
+            MouseButton::Right => BTN_RIGHT,
+            MouseButton::Middle => BTN_MIDDLE,
+            MouseButton::X1 => BTN_SIDE,
+            MouseButton::X2 => BTN_EXTRA,
+        }
+    }
+
+    fn key_code(key: Key) -> io::Result<u16> {
+        key.evdev_code().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} has no evdev code", key),
+            )
+        })
+    }
+}
+
+impl InputBackend for UinputBackend {
+    fn press(&self, button: MouseButton) -> io::Result<()> {
+        self.emit(EV_KEY, Self::button_code(button), 1)
+    }
+
+    fn release(&self, button: MouseButton) -> io::Result<()> {
+        self.emit(EV_KEY, Self::button_code(button), 0)
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> io::Result<()> {
+        if !self.has_absolute_axes {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "absolute motion needs a device created with the screen size",
+            ));
+        }
+        evdev::write_event(&self.device, EV_ABS, ABS_X, x)?;
+        evdev::write_event(&self.device, EV_ABS, ABS_Y, y)?;
+        evdev::write_report(&self.device)
+    }
+
+    fn scroll(&self, axis: ScrollAxis, delta: i32) -> io::Result<()> {
+        self.emit(
+            EV_REL,
+            match axis {
+                ScrollAxis::Vertical => REL_WHEEL,
+                ScrollAxis::Horizontal => REL_HWHEEL,
+            },
+            delta,
+        )
+    }
+
+    fn key_down(&self, key: Key) -> io::Result<()> {
+        self.emit(EV_KEY, Self::key_code(key)?, 1)
+    }
+
+    fn key_up(&self, key: Key) -> io::Result<()> {
+        self.emit(EV_KEY, Self::key_code(key)?, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// Held by the tests while they own a pointer, which all have the same
+    /// name.
+    static POINTER: Mutex<()> = Mutex::new(());
+
+    /// Opens the event device named `name` for reading, waiting for udev to
+    /// create its node.
+    fn open_event_device(name: &str) -> File {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            let device = fs::read_dir("/dev/input")
+                .unwrap()
+                .filter_map(|entry| File::open(entry.ok()?.path()).ok())
+                .find(|file| evdev::device_name(file).is_ok_and(|device| device == name));
+            if let Some(device) = device {
+                return device;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("{} cannot be read", name);
+    }
+
+    /// Type, code and value of the next `count` events other than reports.
+    fn read_events(device: &File, count: usize) -> Vec<(u16, u16, i32)> {
+        let mut events = Vec::new();
+        while events.len() < count {
+            let event = evdev::read_event(device).unwrap();
+            if event.type_ != EV_SYN {
+                events.push((event.type_, event.code, event.value));
+            }
+        }
+        events
+    }
+
+    /// Pointer and reader of its events.
+    fn pointer(screen_size: Option<(i32, i32)>) -> (UinputBackend, File) {
+        let backend = match screen_size {
+            Some((width, height)) => UinputBackend::with_screen_size(width, height),
+            None => UinputBackend::new(),
+        };
+        (backend.unwrap(), open_event_device(POINTER_DEVICE_NAME))
+    }
+
+    #[test]
+    #[ignore = "needs /dev/uinput"]
+    fn emits_buttons_and_wheel_ticks() {
+        let _lock = POINTER.lock().unwrap_or_else(|error| error.into_inner());
+        let (backend, device) = pointer(None);
+        backend.press(MouseButton::X1).unwrap();
+        backend.release(MouseButton::X1).unwrap();
+        backend.scroll(ScrollAxis::Vertical, -2).unwrap();
+        backend.scroll(ScrollAxis::Horizontal, 3).unwrap();
+        assert_eq!(
+            read_events(&device, 4),
+            [
+                (EV_KEY, BTN_SIDE, 1),
+                (EV_KEY, BTN_SIDE, 0),
+                (EV_REL, REL_WHEEL, -2),
+                (EV_REL, REL_HWHEEL, 3),
+            ]
+        );
+    }
+
+    #[test]
+    #[ignore = "needs /dev/uinput"]
+    fn moves_on_the_absolute_axes() {
+        let _lock = POINTER.lock().unwrap_or_else(|error| error.into_inner());
+        let (backend, device) = pointer(Some((1920, 1080)));
+        backend.move_to(100, 200).unwrap();
+        assert_eq!(
+            read_events(&device, 2),
+            [(EV_ABS, ABS_X, 100), (EV_ABS, ABS_Y, 200)]
+        );
+    }
+
+    #[test]
+    #[ignore = "needs /dev/uinput"]
+    fn relative_pointers_cannot_move_to_a_point() {
+        let _lock = POINTER.lock().unwrap_or_else(|error| error.into_inner());
+        let error = UinputBackend::new().unwrap().move_to(0, 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,110 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{InputBackend, MouseButton, ScrollAxis};
+use crate::keys::Key;
+use std::io;
+use winapi::shared::windef::POINT;
+use winapi::um::winuser;
+
+/// Injects input through `mouse_event` and `keybd_event`.
+pub struct Win32Backend;
+
+impl Win32Backend {
+    fn mouse_event(flags: u32, data: i32) -> io::Result<()> {
+        unsafe {
+            winuser::mouse_event(flags, 0, 0, data as u32, 0);
+        }
+        Ok(())
+    }
+
+    fn keybd_event(key: Key, flags: u32) -> io::Result<()> {
+        let vk_code = key.vk_code().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} has no virtual-key code", key),
+            )
+        })?;
+        unsafe {
+            winuser::keybd_event(vk_code as u8, 0, flags, 0);
+        }
+        Ok(())
+    }
+}
+
+impl InputBackend for Win32Backend {
+    fn press(&self, button: MouseButton) -> io::Result<()> {
+        match button {
+            MouseButton::Left => Self::mouse_event(winuser::MOUSEEVENTF_LEFTDOWN, 0),
+            MouseButton::Right => Self::mouse_event(winuser::MOUSEEVENTF_RIGHTDOWN, 0),
+            MouseButton::Middle => Self::mouse_event(winuser::MOUSEEVENTF_MIDDLEDOWN, 0),
+            MouseButton::X1 => {
+                Self::mouse_event(winuser::MOUSEEVENTF_XDOWN, winuser::XBUTTON1.into())
+            }
+            MouseButton::X2 => {
+                Self::mouse_event(winuser::MOUSEEVENTF_XDOWN, winuser::XBUTTON2.into())
+            }
+        }
+    }
+
+    fn release(&self, button: MouseButton) -> io::Result<()> {
+        match button {
+            MouseButton::Left => Self::mouse_event(winuser::MOUSEEVENTF_LEFTUP, 0),
+            MouseButton::Right => Self::mouse_event(winuser::MOUSEEVENTF_RIGHTUP, 0),
+            MouseButton::Middle => Self::mouse_event(winuser::MOUSEEVENTF_MIDDLEUP, 0),
+            MouseButton::X1 => {
+                Self::mouse_event(winuser::MOUSEEVENTF_XUP, winuser::XBUTTON1.into())
+            }
+            MouseButton::X2 => {
+                Self::mouse_event(winuser::MOUSEEVENTF_XUP, winuser::XBUTTON2.into())
+            }
+        }
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> io::Result<()> {
+        if unsafe { winuser::SetCursorPos(x, y) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn cursor_position(&self) -> io::Result<(i32, i32)> {
+        let mut point = POINT { x: 0, y: 0 };
+        if unsafe { winuser::GetCursorPos(&mut point) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((point.x, point.y))
+    }
+
+    fn scroll(&self, axis: ScrollAxis, delta: i32) -> io::Result<()> {
+        Self::mouse_event(
+            match axis {
+                ScrollAxis::Vertical => winuser::MOUSEEVENTF_WHEEL,
+                ScrollAxis::Horizontal => winuser::MOUSEEVENTF_HWHEEL,
+            },
+            delta.saturating_mul(winuser::WHEEL_DELTA.into()),
+        )
+    }
+
+    fn key_down(&self, key: Key) -> io::Result<()> {
+        Self::keybd_event(key, 0)
+    }
+
+    fn key_up(&self, key: Key) -> io::Result<()> {
+        Self::keybd_event(key, winuser::KEYEVENTF_KEYUP)
+    }
+}
@@ -0,0 +1,149 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{self as input_backend, InputBackend, MouseButton, ScrollAxis};
+use crate::keys::Key;
+use crate::window::{self, WindowInfo};
+use std::io;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Posts mouse input straight to a window instead of injecting it globally.
+/// Coordinates are relative to the client area of the window, the pointer
+/// does not move and the window does not need the focus. Some applications
+/// ignore posted input.
+pub struct WindowBackend {
+    window: u64,
+    /// Point of the client area that the input is posted to.
+    position: Mutex<(i32, i32)>,
+    /// Last press that could start a double click.
+    last_press: Mutex<Option<Press>>,
+}
+
+struct Press {
+    button: MouseButton,
+    point: (i32, i32),
+    time: Instant,
+}
+
+impl WindowBackend {
+    /// Creates a backend that posts to `window`, at the top left corner of
+    /// its client area until `move_to` is called.
+    pub fn new(window: &WindowInfo) -> Self {
+        Self {
+            window: window.id,
+            position: Mutex::new((0, 0)),
+            last_press: Mutex::new(None),
+        }
+    }
+
+    /// Whether a press of `button` at `point` is the second press of a
+    /// double click, remembering it as the first press otherwise.
+    fn is_double_click(&self, button: MouseButton, point: (i32, i32)) -> bool {
+        let mut last_press = self.last_press.lock().unwrap();
+        let is_double_click = last_press.take().is_some_and(|press| {
+            press.button == button
+                && press.point == point
+                && press.time.elapsed() <= input_backend::double_click_interval()
+        });
+        if !is_double_click {
+            *last_press = Some(Press {
+                button,
+                point,
+                time: Instant::now(),
+            });
+        }
+        is_double_click
+    }
+}
+
+impl InputBackend for WindowBackend {
+    /// A press of the same button at the same point within the double-click
+    /// interval of the previous one is posted as the second press of a
+    /// double click, and the next one starts over, as the system does.
+    fn press(&self, button: MouseButton) -> io::Result<()> {
+        let (x, y) = *self.position.lock().unwrap();
+        let is_double_click = self.is_double_click(button, (x, y));
+        window::post_button(self.window, button, true, is_double_click, x, y)
+    }
+
+    fn release(&self, button: MouseButton) -> io::Result<()> {
+        let (x, y) = *self.position.lock().unwrap();
+        window::post_button(self.window, button, false, false, x, y)
+    }
+
+    /// Moves the point that the input is posted to, in client coordinates.
+    fn move_to(&self, x: i32, y: i32) -> io::Result<()> {
+        *self.position.lock().unwrap() = (x, y);
+        Ok(())
+    }
+
+    fn cursor_position(&self) -> io::Result<(i32, i32)> {
+        Ok(*self.position.lock().unwrap())
+    }
+
+    fn scroll(&self, axis: ScrollAxis, delta: i32) -> io::Result<()> {
+        let (x, y) = *self.position.lock().unwrap();
+        window::post_scroll(self.window, axis, delta, x, y)
+    }
+
+    fn key_down(&self, key: Key) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} cannot be posted to a window", key),
+        ))
+    }
+
+    fn key_up(&self, key: Key) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} cannot be posted to a window", key),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backend() -> WindowBackend {
+        WindowBackend::new(&WindowInfo {
+            id: 0,
+            title: String::new(),
+            class: String::new(),
+            process: String::new(),
+        })
+    }
+
+    #[test]
+    fn every_second_press_at_the_same_point_is_a_double_click() {
+        let backend = backend();
+        let presses: Vec<_> = (0..4)
+            .map(|_| backend.is_double_click(MouseButton::Left, (5, 5)))
+            .collect();
+        assert_eq!(presses, [false, true, false, true]);
+    }
+
+    #[test]
+    fn presses_of_other_buttons_or_points_start_over() {
+        let backend = backend();
+        assert!(!backend.is_double_click(MouseButton::Left, (5, 5)));
+        assert!(!backend.is_double_click(MouseButton::Right, (5, 5)));
+        assert!(!backend.is_double_click(MouseButton::Right, (6, 5)));
+        assert!(backend.is_double_click(MouseButton::Right, (6, 5)));
+    }
+}
@@ -0,0 +1,120 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{InputBackend, MouseButton, ScrollAxis};
+use crate::keys::Key;
+use crate::x11::{self, x11_error};
+use std::convert::TryFrom;
+use std::io;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{self, ConnectionExt as _, Window};
+use x11rb::protocol::xtest::ConnectionExt;
+use x11rb::rust_connection::RustConnection;
+
+/// Offset between evdev codes and the keycodes of the X.Org evdev and
+/// libinput drivers.
+const EVDEV_KEYCODE_OFFSET: u16 = 8;
+
+/// Injects input into an X11 display through the XTest extension.
+pub struct XTestBackend {
+    connection: RustConnection,
+    root: Window,
+}
+
+impl XTestBackend {
+    /// Connects to the display named by `$DISPLAY`.
+    pub fn new() -> io::Result<Self> {
+        Self::connect(None)
+    }
+
+    /// Connects to the display named `display`, such as `:1`.
+    pub fn with_display(display: &str) -> io::Result<Self> {
+        Self::connect(Some(display))
+    }
+
+    fn connect(display: Option<&str>) -> io::Result<Self> {
+        let (connection, screen_number) = x11rb::connect(display).map_err(x11_error)?;
+        connection
+            .xtest_get_version(2, 2)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        let root = connection.setup().roots[screen_number].root;
+        Ok(Self { connection, root })
+    }
+
+    fn fake_input(&self, kind: u8, detail: u8, x: i16, y: i16) -> io::Result<()> {
+        self.connection
+            .xtest_fake_input(kind, detail, x11rb::CURRENT_TIME, self.root, x, y, 0)
+            .map_err(x11_error)?;
+        self.connection.flush().map_err(x11_error)
+    }
+
+    /// X11 keycode of `key`, which only exists for evdev codes up to 247.
+    fn keycode(key: Key) -> io::Result<u8> {
+        key.evdev_code()
+            .and_then(|code| u8::try_from(code + EVDEV_KEYCODE_OFFSET).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} has no X11 keycode", key),
+                )
+            })
+    }
+}
+
+impl InputBackend for XTestBackend {
+    fn press(&self, button: MouseButton) -> io::Result<()> {
+        self.fake_input(xproto::BUTTON_PRESS_EVENT, x11::button(button), 0, 0)
+    }
+
+    fn release(&self, button: MouseButton) -> io::Result<()> {
+        self.fake_input(xproto::BUTTON_RELEASE_EVENT, x11::button(button), 0, 0)
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> io::Result<()> {
+        let (x, y) = x11::point(x, y)?;
+        self.fake_input(xproto::MOTION_NOTIFY_EVENT, 0, x, y)
+    }
+
+    fn cursor_position(&self) -> io::Result<(i32, i32)> {
+        let reply = self
+            .connection
+            .query_pointer(self.root)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        Ok((reply.root_x.into(), reply.root_y.into()))
+    }
+
+    fn scroll(&self, axis: ScrollAxis, delta: i32) -> io::Result<()> {
+        let button = x11::wheel_button(axis, delta);
+        for _ in 0..delta.unsigned_abs() {
+            self.fake_input(xproto::BUTTON_PRESS_EVENT, button, 0, 0)?;
+            self.fake_input(xproto::BUTTON_RELEASE_EVENT, button, 0, 0)?;
+        }
+        Ok(())
+    }
+
+    fn key_down(&self, key: Key) -> io::Result<()> {
+        self.fake_input(xproto::KEY_PRESS_EVENT, Self::keycode(key)?, 0, 0)
+    }
+
+    fn key_up(&self, key: Key) -> io::Result<()> {
+        self.fake_input(xproto::KEY_RELEASE_EVENT, Self::keycode(key)?, 0, 0)
+    }
+}
@@ -0,0 +1,291 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Random or fixed lengths of the pauses and clicks.
+
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Length of a pause or of a click, drawn anew for every click.
+///
+/// The text form is in milliseconds: `100` is fixed, `80..120` is uniform and
+/// `100~15` is normal, optionally followed by bounds as in `100~15/70..130`.
+#[derive(PartialEq, Copy, Clone, Debug, Deserialize, Serialize)]
+// Untagged so that a plain `Duration` written by older versions is still read
+// as `Fixed`. `Normal` goes before `Uniform`, which would match its fields too.
+#[serde(untagged)]
+pub enum Interval {
+    /// Always the same length.
+    Fixed(Duration),
+    /// Normal distribution clamped to `min` and `max`.
+    Normal {
+        mean: Duration,
+        std_dev: Duration,
+        min: Duration,
+        max: Duration,
+    },
+    /// Uniform distribution between both bounds.
+    Uniform { min: Duration, max: Duration },
+}
+
+impl Default for Interval {
+    fn default() -> Self {
+        Interval::Fixed(Duration::ZERO)
+    }
+}
+
+impl From<Duration> for Interval {
+    fn from(duration: Duration) -> Self {
+        Interval::Fixed(duration)
+    }
+}
+
+impl Interval {
+    /// Normal distribution clamped to three standard deviations around the
+    /// mean.
+    pub fn normal(mean: Duration, std_dev: Duration) -> Self {
+        Interval::Normal {
+            mean,
+            std_dev,
+            min: mean.saturating_sub(std_dev.saturating_mul(3)),
+            max: mean.saturating_add(std_dev.saturating_mul(3)),
+        }
+    }
+
+    /// The same distribution with `duration` added to every length, lengths
+    /// that do not fit saturate.
+    pub fn shifted(&self, duration: Duration) -> Self {
+        match *self {
+            Interval::Fixed(length) => Interval::Fixed(length.saturating_add(duration)),
+            Interval::Uniform { min, max } => Interval::Uniform {
+                min: min.saturating_add(duration),
+                max: max.saturating_add(duration),
+            },
+            Interval::Normal {
+                mean,
+                std_dev,
+                min,
+                max,
+            } => Interval::Normal {
+                mean: mean.saturating_add(duration),
+                std_dev,
+                min: min.saturating_add(duration),
+                max: max.saturating_add(duration),
+            },
+        }
+    }
+
+    /// Average length, ignoring the bounds of a normal distribution.
+    pub fn mean(&self) -> Duration {
+        match *self {
+            Interval::Fixed(duration) => duration,
+            Interval::Uniform { min, max } => min / 2 + max / 2,
+            Interval::Normal { mean, .. } => mean,
+        }
+    }
+
+    /// Draws a length, clamped to the bounds of the distribution.
+    pub fn sample(&self, rng: &mut impl Rng) -> Duration {
+        match *self {
+            Interval::Fixed(duration) => duration,
+            Interval::Uniform { min, max } => {
+                let (min, max) = if min <= max { (min, max) } else { (max, min) };
+                rng.gen_range(min..=max)
+            }
+            Interval::Normal {
+                mean,
+                std_dev,
+                min,
+                max,
+            } => {
+                let (min, max) = if min <= max { (min, max) } else { (max, min) };
+                let seconds = Normal::new(mean.as_secs_f64(), std_dev.as_secs_f64())
+                    .unwrap()
+                    .sample(rng);
+                Duration::try_from_secs_f64(seconds)
+                    .unwrap_or(Duration::ZERO)
+                    .clamp(min, max)
+            }
+        }
+    }
+}
+
+/// Milliseconds with up to three decimals, so that 7.5 clicks per second
+/// reads as 133.333.
+fn format_milliseconds(duration: Duration) -> String {
+    let text = format!("{:.3}", duration.as_secs_f64() * 1000.0);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn parse_milliseconds(text: &str) -> Result<Duration, String> {
+    text.trim()
+        .parse::<f64>()
+        .ok()
+        .and_then(|milliseconds| Duration::try_from_secs_f64(milliseconds / 1000.0).ok())
+        .ok_or_else(|| format!("invalid number of milliseconds '{}'", text.trim()))
+}
+
+fn parse_range(text: &str) -> Result<(Duration, Duration), String> {
+    let (min, max) = text
+        .split_once("..")
+        .ok_or_else(|| format!("invalid range '{}'", text))?;
+    Ok((parse_milliseconds(min)?, parse_milliseconds(max)?))
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Interval::Fixed(duration) => write!(f, "{}", format_milliseconds(duration)),
+            Interval::Uniform { min, max } => write!(
+                f,
+                "{}..{}",
+                format_milliseconds(min),
+                format_milliseconds(max)
+            ),
+            Interval::Normal {
+                mean,
+                std_dev,
+                min,
+                max,
+            } => write!(
+                f,
+                "{}~{}/{}..{}",
+                format_milliseconds(mean),
+                format_milliseconds(std_dev),
+                format_milliseconds(min),
+                format_milliseconds(max)
+            ),
+        }
+    }
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if let Some((mean, rest)) = text.split_once('~') {
+            let (std_dev, bounds) = match rest.split_once('/') {
+                Some((std_dev, bounds)) => (std_dev, Some(bounds)),
+                None => (rest, None),
+            };
+            let (mean, std_dev) = (parse_milliseconds(mean)?, parse_milliseconds(std_dev)?);
+            Ok(match bounds {
+                Some(bounds) => {
+                    let (min, max) = parse_range(bounds)?;
+                    Interval::Normal {
+                        mean,
+                        std_dev,
+                        min,
+                        max,
+                    }
+                }
+                None => Interval::normal(mean, std_dev),
+            })
+        } else if text.contains("..") {
+            let (min, max) = parse_range(text)?;
+            Ok(Interval::Uniform { min, max })
+        } else {
+            Ok(Interval::Fixed(parse_milliseconds(text)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn ms(milliseconds: u64) -> Duration {
+        Duration::from_millis(milliseconds)
+    }
+
+    #[test]
+    fn parses_every_form() {
+        assert_eq!("100".parse(), Ok(Interval::Fixed(ms(100))));
+        assert_eq!(
+            "80..120".parse(),
+            Ok(Interval::Uniform {
+                min: ms(80),
+                max: ms(120)
+            })
+        );
+        assert_eq!("100~15".parse(), Ok(Interval::normal(ms(100), ms(15))));
+        assert_eq!(
+            "100~15/70..130".parse(),
+            Ok(Interval::Normal {
+                mean: ms(100),
+                std_dev: ms(15),
+                min: ms(70),
+                max: ms(130)
+            })
+        );
+        assert!("".parse::<Interval>().is_err());
+        assert!("-5".parse::<Interval>().is_err());
+        assert!("100~15/70".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in ["100", "80..120", "100~15/55..145", "0.5"] {
+            let interval: Interval = text.parse().unwrap();
+            assert_eq!(interval.to_string().parse(), Ok(interval));
+        }
+    }
+
+    #[test]
+    fn displays_at_most_three_decimals() {
+        assert_eq!(Interval::Fixed(ms(100)).to_string(), "100");
+        assert_eq!(
+            Interval::Fixed(Duration::from_secs_f64(1.0 / 7.5)).to_string(),
+            "133.333"
+        );
+        assert_eq!(
+            Interval::Uniform {
+                min: Duration::from_micros(500),
+                max: ms(20)
+            }
+            .to_string(),
+            "0.5..20"
+        );
+    }
+
+    #[test]
+    fn huge_lengths_saturate() {
+        let interval: Interval = "1~1e22".parse().unwrap();
+        assert_eq!(interval.shifted(Duration::MAX).mean(), Duration::MAX);
+    }
+
+    #[test]
+    fn samples_stay_within_the_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let interval = Interval::Normal {
+            mean: ms(100),
+            std_dev: ms(50),
+            min: ms(90),
+            max: ms(110),
+        };
+        for _ in 0..1000 {
+            let sample = interval.sample(&mut rng);
+            assert!((ms(90)..=ms(110)).contains(&sample));
+        }
+    }
+}
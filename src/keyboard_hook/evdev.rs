@@ -0,0 +1,340 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use crate::evdev::{self, BTN_MISC, EVIOCGRAB, EV_KEY};
+use crate::input_backend::uinput::{self, UinputBackend};
+use crate::keys::Key;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const INPUT_DIRECTORY: &str = "/dev/input";
+
+/// How often `INPUT_DIRECTORY` is scanned for hot-plugged devices.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether the keyboards should be grabbed, checked by the listener thread at
+/// least every `RESCAN_INTERVAL`.
+static SHOULD_GRAB: AtomicBool = AtomicBool::new(false);
+
+/// Keyboard or mouse that the listener reads.
+struct Device {
+    path: PathBuf,
+    file: File,
+    name: String,
+    is_keyboard: bool,
+    is_grabbed: bool,
+}
+
+impl Device {
+    /// Whether the device passes on the events of the grabbed keyboards,
+    /// which were already dispatched.
+    fn is_forwarder(&self) -> bool {
+        self.name == uinput::KEYBOARD_DEVICE_NAME
+    }
+
+    /// Whether the device is one of the devices created by this program,
+    /// which are never grabbed.
+    fn is_own(&self) -> bool {
+        self.is_forwarder() || self.name == uinput::POINTER_DEVICE_NAME
+    }
+
+    /// Whether the events of the device count as injected, which is the
+    /// case for the devices of this program. Other virtual devices, such as
+    /// the keyboards of remappers like keyd, pass on physical input.
+    fn is_injecting(&self) -> bool {
+        self.name.starts_with("rsautoclicker")
+    }
+
+    fn is_grabbable(&self) -> bool {
+        self.is_keyboard && !self.is_own()
+    }
+
+    fn set_grabbed(&mut self, is_grabbed: bool) -> io::Result<()> {
+        evdev::ioctl(&self.file, EVIOCGRAB, is_grabbed as libc::c_ulong)?;
+        self.is_grabbed = is_grabbed;
+        Ok(())
+    }
+
+    /// Grabs the keyboard once none of its keys is held down. The releases
+    /// of keys pressed before the grab would only reach the virtual keyboard,
+    /// which never saw their presses, and the keys would stay stuck.
+    fn grab_when_released(&mut self) {
+        if evdev::pressed_keys(&self.file).is_ok_and(|keys| keys.is_empty()) {
+            let _ = self.set_grabbed(true);
+        }
+    }
+}
+
+/// Starts the thread that reads key and button events from every evdev
+/// keyboard and mouse.
+pub fn start() {
+    thread::spawn(run);
+}
+
+/// Checks whether at least one keyboard can be opened for reading, which
+/// usually needs membership in the `input` group.
+pub fn has_readable_keyboard() -> bool {
+    let mut devices = Vec::new();
+    open_devices(&mut devices);
+    devices.iter().any(|device| device.is_keyboard)
+}
+
+/// Makes the listener take the keyboards from the other applications and
+/// pass on the key events that are not consumed through a virtual keyboard.
+pub fn set_grabbed(is_grabbed: bool) {
+    SHOULD_GRAB.store(is_grabbed, Ordering::SeqCst);
+}
+
+fn run() {
+    let mut devices: Vec<Device> = Vec::new();
+    let mut last_scan: Option<Instant> = None;
+    // Present while the keyboards are grabbed.
+    let mut forwarder: Option<UinputBackend> = None;
+    loop {
+        let should_grab = SHOULD_GRAB.load(Ordering::SeqCst);
+        if should_grab != forwarder.is_some() {
+            // Without a virtual keyboard the grabbed keys would be lost, so
+            // the keyboards are left alone when it cannot be created.
+            forwarder = if should_grab {
+                UinputBackend::keyboard().ok()
+            } else {
+                None
+            };
+            if forwarder.is_none() {
+                for device in devices.iter_mut().filter(|device| device.is_grabbed) {
+                    let _ = device.set_grabbed(false);
+                }
+            }
+        }
+
+        if last_scan.is_none_or(|last_scan| last_scan.elapsed() >= RESCAN_INTERVAL) {
+            open_devices(&mut devices);
+            last_scan = Some(Instant::now());
+        }
+        // Keyboards with keys held down are grabbed once the keys are
+        // released, which wakes up the poll below.
+        if forwarder.is_some() {
+            for device in devices
+                .iter_mut()
+                .filter(|device| device.is_grabbable() && !device.is_grabbed)
+            {
+                device.grab_when_released();
+            }
+        }
+
+        let mut poll_fds: Vec<libc::pollfd> = devices
+            .iter()
+            .map(|device| libc::pollfd {
+                fd: device.file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect();
+        unsafe {
+            libc::poll(
+                poll_fds.as_mut_ptr(),
+                poll_fds.len() as libc::nfds_t,
+                RESCAN_INTERVAL.as_millis() as libc::c_int,
+            );
+        }
+
+        let mut unplugged = Vec::new();
+        for (index, poll_fd) in poll_fds.iter().enumerate() {
+            let is_unplugged = poll_fd.revents & (libc::POLLERR | libc::POLLHUP | libc::POLLNVAL)
+                != 0
+                || (poll_fd.revents & libc::POLLIN != 0
+                    && read_events(&devices[index], forwarder.as_ref()).is_err());
+            if is_unplugged {
+                unplugged.push(index);
+            }
+        }
+        for index in unplugged.into_iter().rev() {
+            devices.remove(index);
+        }
+    }
+}
+
+fn open_devices(devices: &mut Vec<Device>) {
+    let entries = match fs::read_dir(INPUT_DIRECTORY) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if !is_event_device(&path) || devices.iter().any(|device| device.path == path) {
+            continue;
+        }
+        if let Ok(file) = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+        {
+            let codes = evdev::supported_codes(&file, EV_KEY).unwrap_or_default();
+            let is_keyboard = codes.iter().any(|code| (1..BTN_MISC).contains(code));
+            let has_mouse_buttons = codes
+                .iter()
+                .any(|code| Key::from_evdev_code(*code).is_mouse_button());
+            if is_keyboard || has_mouse_buttons {
+                let name = evdev::device_name(&file).unwrap_or_default();
+                devices.push(Device {
+                    path,
+                    file,
+                    name,
+                    is_keyboard,
+                    is_grabbed: false,
+                });
+            }
+        }
+    }
+}
+
+fn is_event_device(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("event"))
+}
+
+/// Drains the pending events of `device`, passing on the ones that are not
+/// consumed through `forwarder` when it is grabbed. Fails once the device is
+/// gone.
+fn read_events(device: &Device, forwarder: Option<&UinputBackend>) -> io::Result<()> {
+    let forwarder = forwarder.filter(|_| device.is_grabbed);
+    loop {
+        match evdev::read_event(&device.file) {
+            Ok(_) if device.is_forwarder() => {}
+            Ok(event)
+                if event.type_ == EV_KEY && Key::from_evdev_code(event.code).is_mouse_button() =>
+            {
+                super::dispatch_button(
+                    Key::from_evdev_code(event.code),
+                    event.value != 0,
+                    device.is_injecting(),
+                );
+                if let Some(forwarder) = forwarder {
+                    let _ = forwarder.forward(&event);
+                }
+            }
+            // Value 0 is a release, 1 a press and 2 an auto-repeat, which is
+            // another key-down.
+            Ok(event) if event.type_ == EV_KEY => {
+                let is_consumed = super::dispatch(
+                    Key::from_evdev_code(event.code),
+                    event.value != 0,
+                    device.is_injecting(),
+                );
+                if let Some(forwarder) = forwarder.filter(|_| !is_consumed) {
+                    let _ = forwarder.forward(&event);
+                }
+            }
+            Ok(event) => {
+                if let Some(forwarder) = forwarder {
+                    let _ = forwarder.forward(&event);
+                }
+            }
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotkey::{Modifier, Side};
+    use crate::input_backend::InputBackend;
+    use crate::keyboard_hook::{KeyEvent, KeyboardHook};
+    use crate::keys::Keys;
+    use std::sync::{mpsc, Arc, Mutex};
+
+    #[test]
+    #[ignore = "needs /dev/uinput and /dev/input"]
+    fn hook_reads_keys_from_an_evdev_keyboard() {
+        let keyboard = UinputBackend::keyboard_named("rsautoclicker test keyboard").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !has_readable_keyboard() {
+            assert!(
+                Instant::now() < deadline,
+                "the test keyboard cannot be read"
+            );
+            thread::sleep(Duration::from_millis(50));
+        }
+        let (sender, receiver) = mpsc::channel::<KeyEvent>();
+        let sender = Mutex::new(sender);
+        let mut hook = KeyboardHook::new(Arc::new(move |event| {
+            let _ = sender.lock().unwrap().send(event);
+        }))
+        .unwrap();
+        hook.start();
+
+        // The listener only reads the keyboard once it has scanned the
+        // devices, the keys typed before are lost.
+        let type_key = |key: Keys| {
+            keyboard.key_down(key.into()).unwrap();
+            keyboard.key_up(key.into()).unwrap();
+        };
+        // Other tests may type keys of their own.
+        let keys = [Keys::LControlKey, Keys::F24];
+        let receive = |timeout| {
+            let deadline = Instant::now() + timeout;
+            std::iter::from_fn(|| {
+                receiver
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .ok()
+            })
+            .find(|event: &KeyEvent| keys.iter().any(|key| event.key == (*key).into()))
+        };
+        let first = (0..50).find_map(|_| {
+            type_key(Keys::F24);
+            receive(Duration::from_millis(100))
+        });
+        let first = first.expect("no key event was received");
+        assert_eq!(first.key, Keys::F24.into());
+        assert!(first.is_pressed && first.is_injected);
+        while receive(Duration::from_millis(100)).is_some() {}
+
+        keyboard.key_down(Keys::LControlKey.into()).unwrap();
+        type_key(Keys::F24);
+        keyboard.key_up(Keys::LControlKey.into()).unwrap();
+        let events: Vec<_> = (0..4)
+            .map_while(|_| receive(Duration::from_secs(1)))
+            .collect();
+        let keys: Vec<_> = events
+            .iter()
+            .map(|event| (event.key, event.is_pressed))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                (Keys::LControlKey.into(), true),
+                (Keys::F24.into(), true),
+                (Keys::F24.into(), false),
+                (Keys::LControlKey.into(), false),
+            ]
+        );
+        assert!(events[1]
+            .modifiers
+            .is_pressed(Modifier::Control, Side::Left));
+        hook.stop();
+    }
+}
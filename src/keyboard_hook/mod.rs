@@ -0,0 +1,462 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Global listeners of key and mouse button events.
+
+#[cfg(all(target_os = "linux", feature = "backend-uinput"))]
+mod evdev;
+#[cfg(all(windows, feature = "backend-win32"))]
+mod win32;
+#[cfg(all(target_os = "linux", feature = "backend-x11"))]
+mod x11;
+
+use crate::error::Error;
+use crate::hotkey::{Hotkey, Modifier, ModifierKeys};
+use crate::keys::Key;
+use lazy_static::lazy_static;
+use std::collections::HashSet;
+#[cfg(target_os = "linux")]
+use std::io;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// Press or release of a key. Auto-repeated key-downs are presses with
+/// `is_repeat` set, and every release follows a press.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct KeyEvent {
+    pub key: Key,
+    /// Whether the key went down, `false` for a release.
+    pub is_pressed: bool,
+    /// Whether the event is a key-down repeated while the key is held down.
+    pub is_repeat: bool,
+    /// Whether the event was injected by software instead of typed on a
+    /// physical keyboard.
+    pub is_injected: bool,
+    /// Modifiers held down at the time of the event, not counting `key`.
+    pub modifiers: ModifierKeys,
+}
+
+/// Function called with every key event while its hook is started.
+pub type Callback = Arc<dyn Fn(KeyEvent) + Send + Sync + 'static>;
+
+/// Callback and consumed hotkeys of a started hook.
+struct Registration {
+    callback: Callback,
+    consumed_hotkeys: Vec<Hotkey>,
+}
+
+lazy_static! {
+    static ref REGISTRATIONS: Mutex<Vec<Registration>> = Mutex::new(Vec::new());
+    static ref MODIFIER_KEYS: Mutex<ModifierKeys> = Mutex::new(Default::default());
+    /// Keys that are held down, to tell repeats from presses.
+    static ref PRESSED_KEYS: Mutex<HashSet<Key>> = Mutex::new(HashSet::new());
+    /// Keys whose press was consumed, so that their release is consumed too.
+    static ref CONSUMED_KEYS: Mutex<HashSet<Key>> = Mutex::new(HashSet::new());
+    static ref MOUSE_CALLBACKS: Mutex<Vec<Callback>> = Mutex::new(Vec::new());
+    /// Queue of the thread that calls the mouse hooks. A mouse hotkey may
+    /// stop a clicker, which waits for its click to pass the listener.
+    static ref BUTTON_EVENTS: Mutex<mpsc::Sender<KeyEvent>> = {
+        let (sender, receiver) = mpsc::channel::<KeyEvent>();
+        thread::spawn(move || {
+            for event in receiver {
+                let callbacks = MOUSE_CALLBACKS.lock().unwrap().clone();
+                for callback in callbacks {
+                    callback(event);
+                }
+            }
+        });
+        Mutex::new(sender)
+    };
+}
+
+#[cfg(target_os = "linux")]
+lazy_static! {
+    /// Why no listener could be started, checked by every new hook.
+    static ref INIT_ERROR: Option<String> = start_listener();
+}
+
+/// Starts the evdev listener when a keyboard is readable and the X11 one
+/// otherwise.
+#[cfg(target_os = "linux")]
+fn start_listener() -> Option<String> {
+    let mut reasons: Vec<String> = Vec::new();
+    #[cfg(feature = "backend-uinput")]
+    {
+        if evdev::has_readable_keyboard() {
+            evdev::start();
+            return None;
+        }
+        reasons.push("no keyboard in /dev/input is readable".to_string());
+    }
+    #[cfg(feature = "backend-x11")]
+    match x11::start() {
+        Ok(()) => return None,
+        Err(error) => reasons.push(format!("the X11 display cannot be opened: {}", error)),
+    }
+    if reasons.is_empty() {
+        reasons.push("no listener is built in".to_string());
+    }
+    Some(reasons.join(" and "))
+}
+
+/// Global keyboard listener. Every started hook receives all key presses and
+/// releases, whichever application has the focus.
+pub struct KeyboardHook {
+    callback: Callback,
+    consumed_hotkeys: Vec<Hotkey>,
+    is_working: bool,
+}
+
+impl Drop for KeyboardHook {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl KeyboardHook {
+    #[cfg(windows)]
+    fn init() -> Result<(), Error> {
+        #[cfg(feature = "backend-win32")]
+        win32::init().map_err(Error::Hook)?;
+        Ok(())
+    }
+
+    /// Prefers evdev keyboards and falls back to the X11 display when none of
+    /// them is readable. Fails when neither can be read, as no hotkey would
+    /// ever be seen.
+    #[cfg(target_os = "linux")]
+    fn init() -> Result<(), Error> {
+        match &*INIT_ERROR {
+            Some(reason) => Err(Error::Hook(io::Error::other(reason.clone()))),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    fn init() -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Creates a stopped hook, installing the platform listener on first use.
+    pub fn new(callback: Callback) -> Result<Self, Error> {
+        Self::init()?;
+        Ok(Self {
+            callback,
+            consumed_hotkeys: Vec::new(),
+            is_working: false,
+        })
+    }
+
+    /// Starts passing key events to the callback.
+    pub fn start(&mut self) {
+        if !self.is_working {
+            self.is_working = true;
+            let mut lock = REGISTRATIONS.lock().unwrap();
+            lock.push(Registration {
+                callback: self.callback.clone(),
+                consumed_hotkeys: self.consumed_hotkeys.clone(),
+            });
+            update_grabs(&lock);
+        }
+    }
+
+    /// Stops passing key events to the callback.
+    pub fn stop(&mut self) {
+        if self.is_working {
+            self.is_working = false;
+            let mut lock = REGISTRATIONS.lock().unwrap();
+            let position = lock
+                .iter()
+                .position(|registration| Arc::ptr_eq(&registration.callback, &self.callback))
+                .unwrap();
+            lock.remove(position);
+            update_grabs(&lock);
+        }
+    }
+
+    /// Sets the hotkeys whose key events are hidden from the other
+    /// applications while the hook is started. The callback still receives
+    /// them.
+    pub fn set_consumed_hotkeys(&mut self, hotkeys: Vec<Hotkey>) {
+        self.consumed_hotkeys = hotkeys;
+        if self.is_working {
+            let mut lock = REGISTRATIONS.lock().unwrap();
+            if let Some(registration) = lock
+                .iter_mut()
+                .find(|registration| Arc::ptr_eq(&registration.callback, &self.callback))
+            {
+                registration.consumed_hotkeys = self.consumed_hotkeys.clone();
+            }
+            update_grabs(&lock);
+        }
+    }
+}
+
+/// Global listener of the mouse buttons, which reports them as key events of
+/// `Keys::LButton`, `Keys::XButton1` etc. with the modifiers held down.
+/// Injected clicks, such as the clicker's own, are flagged like injected keys
+/// and no event is consumed. The callback is called on a thread of its own.
+pub struct MouseHook {
+    callback: Callback,
+    is_working: bool,
+}
+
+impl Drop for MouseHook {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl MouseHook {
+    /// Creates a stopped hook, installing the platform listeners on first use.
+    /// The keyboard listener is installed too, for the state of the
+    /// modifiers.
+    pub fn new(callback: Callback) -> Result<Self, Error> {
+        KeyboardHook::init()?;
+        #[cfg(all(windows, feature = "backend-win32"))]
+        win32::init_mouse().map_err(Error::Hook)?;
+        Ok(Self {
+            callback,
+            is_working: false,
+        })
+    }
+
+    /// Starts passing button events to the callback.
+    pub fn start(&mut self) {
+        if !self.is_working {
+            self.is_working = true;
+            MOUSE_CALLBACKS.lock().unwrap().push(self.callback.clone());
+        }
+    }
+
+    /// Stops passing button events to the callback.
+    pub fn stop(&mut self) {
+        if self.is_working {
+            self.is_working = false;
+            let mut lock = MOUSE_CALLBACKS.lock().unwrap();
+            let position = lock
+                .iter()
+                .position(|callback| Arc::ptr_eq(callback, &self.callback))
+                .unwrap();
+            lock.remove(position);
+        }
+    }
+}
+
+/// Tells the platform listener which keys to take from the other
+/// applications, on Windows the hook decides for every event instead.
+#[cfg_attr(
+    not(all(
+        target_os = "linux",
+        any(feature = "backend-uinput", feature = "backend-x11")
+    )),
+    allow(unused_variables)
+)]
+fn update_grabs(registrations: &[Registration]) {
+    #[cfg(all(
+        target_os = "linux",
+        any(feature = "backend-uinput", feature = "backend-x11")
+    ))]
+    let hotkeys: Vec<Hotkey> = registrations
+        .iter()
+        .flat_map(|registration| registration.consumed_hotkeys.iter().copied())
+        .collect();
+    #[cfg(all(target_os = "linux", feature = "backend-uinput"))]
+    evdev::set_grabbed(!hotkeys.is_empty());
+    #[cfg(all(target_os = "linux", feature = "backend-x11"))]
+    x11::grab(&hotkeys);
+}
+
+/// Passes a key event to every started hook and returns whether it is
+/// consumed. A press is consumed when it matches a consumed hotkey, and so
+/// are its auto-repeats and its release. Key-downs of a held key are marked
+/// as repeats, and releases of a key that is not held are dropped.
+#[cfg_attr(
+    not(any(
+        feature = "backend-win32",
+        feature = "backend-uinput",
+        feature = "backend-x11"
+    )),
+    allow(dead_code)
+)]
+fn dispatch(key: Key, is_pressed: bool, is_injected: bool) -> bool {
+    let is_repeat = {
+        let mut pressed_keys = PRESSED_KEYS.lock().unwrap();
+        if is_pressed {
+            !pressed_keys.insert(key)
+        } else if !pressed_keys.remove(&key) {
+            return false;
+        } else {
+            false
+        }
+    };
+    let modifiers = {
+        let mut modifier_keys = MODIFIER_KEYS.lock().unwrap();
+        let mut modifiers = *modifier_keys;
+        if let Some((modifier, side)) = Modifier::of_key(key) {
+            modifier_keys.set(modifier, side, is_pressed);
+            modifiers.set(modifier, side, false);
+        }
+        modifiers
+    };
+    let event = KeyEvent {
+        key,
+        is_pressed,
+        is_repeat,
+        is_injected,
+        modifiers,
+    };
+    // Copied out so that the callbacks can start and stop hooks.
+    let (callbacks, consumed_hotkeys): (Vec<Callback>, Vec<Hotkey>) = {
+        let registrations = REGISTRATIONS.lock().unwrap();
+        (
+            registrations
+                .iter()
+                .map(|registration| registration.callback.clone())
+                .collect(),
+            registrations
+                .iter()
+                .flat_map(|registration| registration.consumed_hotkeys.iter().copied())
+                .collect(),
+        )
+    };
+    for callback in callbacks {
+        callback(event);
+    }
+    let mut consumed_keys = CONSUMED_KEYS.lock().unwrap();
+    if is_pressed {
+        let is_consumed = consumed_keys.contains(&key)
+            || consumed_hotkeys.iter().any(|hotkey| hotkey.matches(&event));
+        if is_consumed {
+            consumed_keys.insert(key);
+        }
+        is_consumed
+    } else {
+        consumed_keys.remove(&key)
+    }
+}
+
+/// Passes a press or release of a mouse button to every started mouse hook,
+/// on their own thread so that the platform listener does not wait for them.
+#[cfg_attr(
+    not(any(
+        feature = "backend-win32",
+        feature = "backend-uinput",
+        feature = "backend-x11"
+    )),
+    allow(dead_code)
+)]
+fn dispatch_button(key: Key, is_pressed: bool, is_injected: bool) {
+    let event = KeyEvent {
+        key,
+        is_pressed,
+        is_repeat: false,
+        is_injected,
+        modifiers: *MODIFIER_KEYS.lock().unwrap(),
+    };
+    let _ = BUTTON_EVENTS.lock().unwrap().send(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotkey::Binding;
+    use crate::keys::Keys;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn presses_of_a_held_key_are_repeats() {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let mut hook = KeyboardHook {
+            callback: Arc::new(move |event| {
+                let _ = sender.lock().unwrap().send(event);
+            }),
+            consumed_hotkeys: Vec::new(),
+            is_working: false,
+        };
+        hook.start();
+        let key = Key::from(Keys::F21);
+        dispatch(key, true, false);
+        dispatch(key, true, false);
+        dispatch(key, false, false);
+        // The release of a key that is not held is dropped.
+        dispatch(key, false, false);
+        hook.stop();
+
+        // Other tests may dispatch keys of their own.
+        let events: Vec<_> = receiver
+            .try_iter()
+            .filter(|event: &KeyEvent| event.key == key)
+            .collect();
+        let order: Vec<_> = events
+            .iter()
+            .map(|event| (event.is_pressed, event.is_repeat))
+            .collect();
+        assert_eq!(order, [(true, false), (true, true), (false, false)]);
+        let binding = Binding::from(Hotkey::from(Keys::F21));
+        let matches: Vec<_> = events.iter().map(|event| binding.matches(event)).collect();
+        assert_eq!(matches, [true, false, true]);
+    }
+
+    #[test]
+    fn callbacks_can_start_and_stop_hooks() {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let mut hook = KeyboardHook {
+            callback: Arc::new(move |event: KeyEvent| {
+                if event.key == Keys::F20.into() {
+                    let mut other = KeyboardHook {
+                        callback: Arc::new(|_| {}),
+                        consumed_hotkeys: Vec::new(),
+                        is_working: false,
+                    };
+                    other.start();
+                    other.stop();
+                    let _ = sender.lock().unwrap().send(event);
+                }
+            }),
+            consumed_hotkeys: Vec::new(),
+            is_working: false,
+        };
+        hook.start();
+        dispatch(Keys::F20.into(), true, false);
+        dispatch(Keys::F20.into(), false, false);
+        hook.stop();
+        assert_eq!(receiver.try_iter().count(), 2);
+    }
+
+    #[test]
+    fn mouse_hooks_are_called_on_their_own_thread() {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let mut hook = MouseHook {
+            callback: Arc::new(move |event: KeyEvent| {
+                if event.key == Keys::XButton2.into() {
+                    let _ = sender.lock().unwrap().send(thread::current().id());
+                }
+            }),
+            is_working: false,
+        };
+        hook.start();
+        dispatch_button(Keys::XButton2.into(), true, false);
+        let thread_id = receiver.recv_timeout(Duration::from_secs(5));
+        hook.stop();
+        assert!(thread_id.is_ok_and(|thread_id| thread_id != thread::current().id()));
+    }
+}
@@ -0,0 +1,154 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::keys::{Key, Keys};
+use lazy_static::lazy_static;
+use std::io;
+use std::mem;
+use std::ptr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use winapi::ctypes::c_int;
+use winapi::shared::minwindef::{LPARAM, LRESULT, WPARAM};
+use winapi::shared::windef::HHOOK;
+use winapi::um::{libloaderapi, winuser};
+
+lazy_static! {
+    static ref HOOK_HANDLE: Mutex<Option<usize>> = Mutex::new(None);
+    static ref MOUSE_HOOK_HANDLE: Mutex<Option<usize>> = Mutex::new(None);
+}
+
+/// Installs the low-level keyboard hook on its own message loop thread.
+pub fn init() -> io::Result<()> {
+    install(winuser::WH_KEYBOARD_LL, callback, &HOOK_HANDLE)
+}
+
+/// Installs the low-level mouse hook on its own message loop thread.
+pub fn init_mouse() -> io::Result<()> {
+    install(winuser::WH_MOUSE_LL, mouse_callback, &MOUSE_HOOK_HANDLE)
+}
+
+fn install(
+    hook_id: c_int,
+    procedure: unsafe extern "system" fn(c_int, WPARAM, LPARAM) -> LRESULT,
+    handle: &Mutex<Option<usize>>,
+) -> io::Result<()> {
+    let mut hook_handle_lock = handle.lock().unwrap();
+    if hook_handle_lock.is_none() {
+        let (mutex, condvar) = (Arc::new(Mutex::new(None)), Arc::new(Condvar::new()));
+        let (cloned_mutex, cloned_condvar) = (mutex.clone(), condvar.clone());
+        thread::spawn(move || {
+            {
+                let hook_handle = unsafe {
+                    winuser::SetWindowsHookExA(
+                        hook_id,
+                        Some(procedure),
+                        libloaderapi::LoadLibraryA("User32".as_ptr() as *const i8),
+                        0,
+                    )
+                };
+                *cloned_mutex.lock().unwrap() = Some(if hook_handle.is_null() {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(hook_handle as usize)
+                });
+                cloned_condvar.notify_one();
+                if hook_handle.is_null() {
+                    return;
+                }
+            }
+            unsafe {
+                let mut msg: winuser::MSG = mem::zeroed();
+                while winuser::GetMessageA(&mut msg, ptr::null_mut(), 0, 0) != 0 {
+                    winuser::TranslateMessage(&msg);
+                    winuser::DispatchMessageA(&msg);
+                }
+            }
+        });
+        let mut lock = mutex.lock().unwrap();
+        while lock.is_none() {
+            lock = condvar.wait(lock).unwrap();
+        }
+        *hook_handle_lock = Some(lock.take().unwrap()?);
+    }
+    Ok(())
+}
+
+unsafe extern "system" fn callback(n_code: i32, w_param: usize, l_param: isize) -> isize {
+    let key_info: winuser::KBDLLHOOKSTRUCT = *(l_param as *const winuser::KBDLLHOOKSTRUCT);
+    if n_code == winuser::HC_ACTION {
+        let is_pressed = match w_param as u32 {
+            winuser::WM_KEYDOWN | winuser::WM_SYSKEYDOWN => Some(true),
+            winuser::WM_KEYUP | winuser::WM_SYSKEYUP => Some(false),
+            _ => None,
+        };
+        if let Some(is_pressed) = is_pressed {
+            if super::dispatch(
+                Key::from_vk_code(key_info.vkCode),
+                is_pressed,
+                key_info.flags & winuser::LLKHF_INJECTED != 0,
+            ) {
+                // A non-zero result keeps the event from the other hooks and
+                // the focused window.
+                return 1;
+            }
+        }
+    }
+    winuser::CallNextHookEx(
+        HOOK_HANDLE.lock().unwrap().unwrap() as HHOOK,
+        n_code,
+        w_param,
+        l_param,
+    )
+}
+
+unsafe extern "system" fn mouse_callback(n_code: i32, w_param: usize, l_param: isize) -> isize {
+    let mouse_info: winuser::MSLLHOOKSTRUCT = *(l_param as *const winuser::MSLLHOOKSTRUCT);
+    if n_code == winuser::HC_ACTION {
+        let button = match w_param as u32 {
+            winuser::WM_LBUTTONDOWN => Some((Keys::LButton, true)),
+            winuser::WM_LBUTTONUP => Some((Keys::LButton, false)),
+            winuser::WM_RBUTTONDOWN => Some((Keys::RButton, true)),
+            winuser::WM_RBUTTONUP => Some((Keys::RButton, false)),
+            winuser::WM_MBUTTONDOWN => Some((Keys::MButton, true)),
+            winuser::WM_MBUTTONUP => Some((Keys::MButton, false)),
+            message @ (winuser::WM_XBUTTONDOWN | winuser::WM_XBUTTONUP) => Some((
+                // The high word of mouseData tells the side buttons apart.
+                if (mouse_info.mouseData >> 16) as u16 == winuser::XBUTTON1 {
+                    Keys::XButton1
+                } else {
+                    Keys::XButton2
+                },
+                message == winuser::WM_XBUTTONDOWN,
+            )),
+            _ => None,
+        };
+        if let Some((key, is_pressed)) = button {
+            super::dispatch_button(
+                key.into(),
+                is_pressed,
+                mouse_info.flags & winuser::LLMHF_INJECTED != 0,
+            );
+        }
+    }
+    winuser::CallNextHookEx(
+        MOUSE_HOOK_HANDLE.lock().unwrap().unwrap() as HHOOK,
+        n_code,
+        w_param,
+        l_param,
+    )
+}
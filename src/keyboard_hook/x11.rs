@@ -0,0 +1,263 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::hotkey::{Hotkey, Modifier};
+use crate::keys::{Key, Keys};
+use crate::x11::x11_error;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use strum::IntoEnumIterator;
+use x11rb::connection::Connection;
+use x11rb::protocol::xinput::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::{ConnectionExt as _, Grab, GrabMode, ModMask, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+/// Offset between X11 keycodes and evdev codes, see `XTestBackend`.
+const EVDEV_KEYCODE_OFFSET: u32 = 8;
+
+lazy_static! {
+    /// Connection of the listener thread and its root window, used to grab
+    /// the consumed hotkeys.
+    static ref CONNECTION: Mutex<Option<(Arc<RustConnection>, Window)>> = Mutex::new(None);
+}
+
+/// Listens to the key and button events of `$DISPLAY`.
+pub fn start() -> io::Result<()> {
+    let (connection, root) = connect(None)?;
+    *CONNECTION.lock().unwrap() = Some((connection.clone(), root));
+    listen(connection);
+    Ok(())
+}
+
+/// Connects to `display`, or to `$DISPLAY` when it is `None`, and subscribes
+/// to XInput2 raw key and button presses and releases on its root window.
+/// Changes of the device hierarchy are selected too, as the server reuses
+/// the ids of removed devices.
+fn connect(display: Option<&str>) -> io::Result<(Arc<RustConnection>, Window)> {
+    let (connection, screen_number) = x11rb::connect(display).map_err(x11_error)?;
+    connection
+        .xinput_xi_query_version(2, 2)
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?;
+    let root = connection.setup().roots[screen_number].root;
+    connection
+        .xinput_xi_select_events(
+            root,
+            &[
+                xinput::EventMask {
+                    deviceid: xinput::Device::ALL_MASTER.into(),
+                    mask: vec![
+                        xinput::XIEventMask::RAW_KEY_PRESS
+                            | xinput::XIEventMask::RAW_KEY_RELEASE
+                            | xinput::XIEventMask::RAW_BUTTON_PRESS
+                            | xinput::XIEventMask::RAW_BUTTON_RELEASE,
+                    ],
+                },
+                xinput::EventMask {
+                    deviceid: xinput::Device::ALL.into(),
+                    mask: vec![xinput::XIEventMask::HIERARCHY],
+                },
+            ],
+        )
+        .map_err(x11_error)?
+        .check()
+        .map_err(x11_error)?;
+
+    Ok((Arc::new(connection), root))
+}
+
+/// Starts the thread that dispatches the events of `connection`, which ends
+/// when the connection does.
+fn listen(connection: Arc<RustConnection>) {
+    thread::spawn(move || {
+        let mut injecting_devices = HashMap::new();
+        while let Ok(event) = connection.wait_for_event() {
+            let mut is_injected = |device| {
+                *injecting_devices
+                    .entry(device)
+                    .or_insert_with(|| is_injecting(&connection, device))
+            };
+            match event {
+                Event::XinputRawKeyPress(event) => {
+                    dispatch_key(event.detail, true, is_injected(event.sourceid))
+                }
+                Event::XinputRawKeyRelease(event) => {
+                    dispatch_key(event.detail, false, is_injected(event.sourceid))
+                }
+                Event::XinputRawButtonPress(event) => {
+                    dispatch_button(event.detail, true, is_injected(event.sourceid))
+                }
+                Event::XinputRawButtonRelease(event) => {
+                    dispatch_button(event.detail, false, is_injected(event.sourceid))
+                }
+                Event::XinputHierarchy(_) => injecting_devices.clear(),
+                _ => {}
+            }
+        }
+    });
+}
+
+fn dispatch_key(keycode: u32, is_pressed: bool, is_injected: bool) {
+    if let Some(code) = keycode.checked_sub(EVDEV_KEYCODE_OFFSET) {
+        super::dispatch(Key::from_evdev_code(code as u16), is_pressed, is_injected);
+    }
+}
+
+/// Dispatches a press or release of a core button, the wheel and other
+/// buttons without a key are left out.
+fn dispatch_button(button: u32, is_pressed: bool, is_injected: bool) {
+    if let Some(button) = crate::x11::mouse_button(button) {
+        super::dispatch_button(Keys::from(button).into(), is_pressed, is_injected);
+    }
+}
+
+/// Whether `device` injects input, as the XTest devices of the server and
+/// the uinput pointers of the clicker do.
+fn is_injecting(connection: &RustConnection, device: u16) -> bool {
+    connection
+        .xinput_xi_query_device(device)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .and_then(|reply| reply.infos.into_iter().next())
+        .is_some_and(|info| {
+            let name = String::from_utf8_lossy(&info.name);
+            name.contains("XTEST") || name.starts_with("rsautoclicker")
+        })
+}
+
+/// Replaces the passive grabs on the root window with grabs of `hotkeys`, so
+/// that their key events are only reported to the listener. Sides of
+/// modifiers are not told apart, and Caps Lock and Num Lock are ignored.
+pub fn grab(hotkeys: &[Hotkey]) {
+    let lock = CONNECTION.lock().unwrap();
+    let (connection, root) = match &*lock {
+        Some((connection, root)) => (connection, *root),
+        None => return,
+    };
+    if let Ok(cookie) = connection.ungrab_key(Grab::ANY, root, ModMask::ANY) {
+        cookie.ignore_error();
+    }
+    for hotkey in hotkeys {
+        let keycode = match hotkey
+            .key
+            .evdev_code()
+            .and_then(|code| u8::try_from(code as u32 + EVDEV_KEYCODE_OFFSET).ok())
+        {
+            Some(keycode) => keycode,
+            None => continue,
+        };
+        let modifiers = Modifier::iter()
+            .filter(|modifier| hotkey.modifier(*modifier).is_some())
+            .fold(ModMask::from(0u16), |mask, modifier| {
+                mask | match modifier {
+                    Modifier::Control => ModMask::CONTROL,
+                    Modifier::Shift => ModMask::SHIFT,
+                    Modifier::Alt => ModMask::M1,
+                    Modifier::Win => ModMask::M4,
+                }
+            });
+        for locks in [
+            ModMask::from(0u16),
+            ModMask::LOCK,
+            ModMask::M2,
+            ModMask::LOCK | ModMask::M2,
+        ] {
+            if let Ok(cookie) = connection.grab_key(
+                false,
+                root,
+                modifiers | locks,
+                keycode,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            ) {
+                // Fails when another client grabs the same key, which leaves
+                // it passed through.
+                cookie.ignore_error();
+            }
+        }
+    }
+    let _ = connection.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotkey::Side;
+    use crate::input_backend::{InputBackend, XTestBackend};
+    use crate::keyboard_hook::{KeyEvent, KeyboardHook};
+    use crate::testing::Xvfb;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    #[ignore = "needs Xvfb"]
+    fn reads_keys_injected_through_xtest() {
+        let xvfb = Xvfb::start();
+        // Leaves the listener of the process to the default display.
+        let (connection, _) = connect(Some(&xvfb.display)).unwrap();
+        listen(connection);
+        // Bypasses `KeyboardHook::init`, which prefers evdev keyboards.
+        let (sender, receiver) = mpsc::channel::<KeyEvent>();
+        let sender = Mutex::new(sender);
+        let mut hook = KeyboardHook {
+            callback: Arc::new(move |event| {
+                let _ = sender.lock().unwrap().send(event);
+            }),
+            consumed_hotkeys: Vec::new(),
+            is_working: false,
+        };
+        hook.start();
+
+        let backend = XTestBackend::with_display(&xvfb.display).unwrap();
+        let keys = [Keys::RControlKey, Keys::F23];
+        backend.key_down(Keys::RControlKey.into()).unwrap();
+        backend.key_down(Keys::F23.into()).unwrap();
+        backend.key_up(Keys::F23.into()).unwrap();
+        backend.key_up(Keys::RControlKey.into()).unwrap();
+        // Other tests may type keys of their own.
+        let events: Vec<_> =
+            std::iter::from_fn(|| receiver.recv_timeout(Duration::from_secs(2)).ok())
+                .filter(|event| keys.iter().any(|key| event.key == (*key).into()))
+                .take(4)
+                .collect();
+        let order: Vec<_> = events
+            .iter()
+            .map(|event| (event.key, event.is_pressed))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (Keys::RControlKey.into(), true),
+                (Keys::F23.into(), true),
+                (Keys::F23.into(), false),
+                (Keys::RControlKey.into(), false),
+            ]
+        );
+        assert!(events.iter().all(|event| event.is_injected));
+        assert!(events[1]
+            .modifiers
+            .is_pressed(Modifier::Control, Side::Right));
+
+        hook.stop();
+    }
+}
@@ -15,182 +15,189 @@
 *   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use num_derive::FromPrimitive;
-use serde::{Deserialize, Serialize};
+//! Platform-neutral names of keys and their native codes.
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+use std::convert::TryFrom;
 use std::fmt;
+use std::str::FromStr;
+use strum::EnumString;
 
-#[derive(FromPrimitive, Clone, Copy, Deserialize, Serialize, Debug)]
+/// Named keys, identified by name only so that serialized settings bind the
+/// same physical key on every platform.
+#[derive(EnumString, PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Keys {
-    LButton = 0x01,
-    RButton = 0x02,
-    Cancel = 0x03,
-    MButton = 0x04,
-    XButton1 = 0x05,
-    XButton2 = 0x06,
-    Back = 0x08,
-    Tab = 0x09,
-    LineFeed = 0x0A,
-    Clear = 0x0C,
-    Return = 0x0D,
-    ShiftKey = 0x10,
-    ControlKey = 0x11,
-    Menu = 0x12,
-    Pause = 0x13,
-    CapsLock = 0x14,
-    HangulMode = 0x15,
-    JunjaMode = 0x17,
-    FinalMode = 0x18,
-    HanjaMode = 0x19,
-    Escape = 0x1B,
-    IMEConvert = 0x1C,
-    IMENonconvert = 0x1D,
-    IMEModeChange = 0x1F,
-    Space = 0x20,
-    PageUp = 0x21,
-    PageDown = 0x22,
-    End = 0x23,
-    Home = 0x24,
-    Left = 0x25,
-    Up = 0x26,
-    Right = 0x27,
-    Down = 0x28,
-    Select = 0x29,
-    Print = 0x2A,
-    Execute = 0x2B,
-    PrintScreen = 0x2C,
-    Insert = 0x2D,
-    Delete = 0x2E,
-    Help = 0x2F,
-    D0 = 0x30,
-    D1 = 0x31,
-    D2 = 0x32,
-    D3 = 0x33,
-    D4 = 0x34,
-    D5 = 0x35,
-    D6 = 0x36,
-    D7 = 0x37,
-    D8 = 0x38,
-    D9 = 0x39,
-    A = 0x41,
-    B = 0x42,
-    C = 0x43,
-    D = 0x44,
-    E = 0x45,
-    F = 0x46,
-    G = 0x47,
-    H = 0x48,
-    I = 0x49,
-    J = 0x4A,
-    K = 0x4B,
-    L = 0x4C,
-    M = 0x4D,
-    N = 0x4E,
-    O = 0x4F,
-    P = 0x50,
-    Q = 0x51,
-    R = 0x52,
-    S = 0x53,
-    T = 0x54,
-    U = 0x55,
-    V = 0x56,
-    W = 0x57,
-    X = 0x58,
-    Y = 0x59,
-    Z = 0x5A,
-    LWin = 0x5B,
-    RWin = 0x5C,
-    Apps = 0x5D,
-    Sleep = 0x5F,
-    NumPad0 = 0x60,
-    NumPad1 = 0x61,
-    NumPad2 = 0x62,
-    NumPad3 = 0x63,
-    NumPad4 = 0x64,
-    NumPad5 = 0x65,
-    NumPad6 = 0x66,
-    NumPad7 = 0x67,
-    NumPad8 = 0x68,
-    NumPad9 = 0x69,
-    Multiply = 0x6A,
-    Add = 0x6B,
-    Separator = 0x6C,
-    Subtract = 0x6D,
-    Decimal = 0x6E,
-    Divide = 0x6F,
-    F1 = 0x70,
-    F2 = 0x71,
-    F3 = 0x72,
-    F4 = 0x73,
-    F5 = 0x74,
-    F6 = 0x75,
-    F7 = 0x76,
-    F8 = 0x77,
-    F9 = 0x78,
-    F10 = 0x79,
-    F11 = 0x7A,
-    F12 = 0x7B,
-    F13 = 0x7C,
-    F14 = 0x7D,
-    F15 = 0x7E,
-    F16 = 0x7F,
-    F17 = 0x80,
-    F18 = 0x81,
-    F19 = 0x82,
-    F20 = 0x83,
-    F21 = 0x84,
-    F22 = 0x85,
-    F23 = 0x86,
-    F24 = 0x87,
-    NumLock = 0x90,
-    Scroll = 0x91,
-    LShiftKey = 0xA0,
-    RShiftKey = 0xA1,
-    LControlKey = 0xA2,
-    RControlKey = 0xA3,
-    LMenu = 0xA4,
-    RMenu = 0xA5,
-    BrowserBack = 0xA6,
-    BrowserForward = 0xA7,
-    BrowserRefresh = 0xA8,
-    BrowserStop = 0xA9,
-    BrowserSearch = 0xAA,
-    BrowserFavorites = 0xAB,
-    BrowserHome = 0xAC,
-    VolumeMute = 0xAD,
-    VolumeDown = 0xAE,
-    VolumeUp = 0xAF,
-    MediaNextTrack = 0xB0,
-    MediaPreviousTrack = 0xB1,
-    MediaStop = 0xB2,
-    MediaPlayPause = 0xB3,
-    LaunchMail = 0xB4,
-    SelectMedia = 0xB5,
-    LaunchApplication1 = 0xB6,
-    LaunchApplication2 = 0xB7,
-    OemSemicolon = 0xBA,
-    Oemplus = 0xBB,
-    Oemcomma = 0xBC,
-    OemMinus = 0xBD,
-    OemPeriod = 0xBE,
-    OemQuestion = 0xBF,
-    Oemtilde = 0xC0,
-    OemOpenBrackets = 0xDB,
-    OemPipe = 0xDC,
-    OemCloseBrackets = 0xDD,
-    OemQuotes = 0xDE,
-    Oem8 = 0xDF,
-    OemBackslash = 0xE2,
-    ProcessKey = 0xE5,
-    Packet = 0xE7,
-    Attn = 0xF6,
-    Crsel = 0xF7,
-    Exsel = 0xF8,
-    EraseEof = 0xF9,
-    Play = 0xFA,
-    Zoom = 0xFB,
-    NoName = 0xFC,
-    Pa1 = 0xFD,
-    OemClear = 0xFE,
+    LButton,
+    RButton,
+    Cancel,
+    MButton,
+    XButton1,
+    XButton2,
+    Back,
+    Tab,
+    LineFeed,
+    Clear,
+    Return,
+    ShiftKey,
+    ControlKey,
+    Menu,
+    Pause,
+    CapsLock,
+    HangulMode,
+    JunjaMode,
+    FinalMode,
+    HanjaMode,
+    Escape,
+    IMEConvert,
+    IMENonconvert,
+    IMEModeChange,
+    Space,
+    PageUp,
+    PageDown,
+    End,
+    Home,
+    Left,
+    Up,
+    Right,
+    Down,
+    Select,
+    Print,
+    Execute,
+    PrintScreen,
+    Insert,
+    Delete,
+    Help,
+    D0,
+    D1,
+    D2,
+    D3,
+    D4,
+    D5,
+    D6,
+    D7,
+    D8,
+    D9,
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    LWin,
+    RWin,
+    Apps,
+    Sleep,
+    NumPad0,
+    NumPad1,
+    NumPad2,
+    NumPad3,
+    NumPad4,
+    NumPad5,
+    NumPad6,
+    NumPad7,
+    NumPad8,
+    NumPad9,
+    Multiply,
+    Add,
+    Separator,
+    Subtract,
+    Decimal,
+    Divide,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    NumLock,
+    Scroll,
+    LShiftKey,
+    RShiftKey,
+    LControlKey,
+    RControlKey,
+    LMenu,
+    RMenu,
+    BrowserBack,
+    BrowserForward,
+    BrowserRefresh,
+    BrowserStop,
+    BrowserSearch,
+    BrowserFavorites,
+    BrowserHome,
+    VolumeMute,
+    VolumeDown,
+    VolumeUp,
+    MediaNextTrack,
+    MediaPreviousTrack,
+    MediaStop,
+    MediaPlayPause,
+    LaunchMail,
+    SelectMedia,
+    LaunchApplication1,
+    LaunchApplication2,
+    OemSemicolon,
+    Oemplus,
+    Oemcomma,
+    OemMinus,
+    OemPeriod,
+    OemQuestion,
+    Oemtilde,
+    OemOpenBrackets,
+    OemPipe,
+    OemCloseBrackets,
+    OemQuotes,
+    Oem8,
+    OemBackslash,
+    ProcessKey,
+    Packet,
+    Attn,
+    Crsel,
+    Exsel,
+    EraseEof,
+    Play,
+    Zoom,
+    NoName,
+    Pa1,
+    OemClear,
 }
 
 impl fmt::Display for Keys {
@@ -198,3 +205,394 @@ impl fmt::Display for Keys {
         write!(f, "{:?}", self)
     }
 }
+
+/// Windows virtual-key code, Linux evdev code and X11 keysym of every key.
+/// Where several keys share a code the first entry wins when converting to
+/// `Keys`, so left-hand modifiers are listed before the generic ones.
+const CODES: &[(Keys, u32, Option<u16>, Option<u32>)] = &[
+    (Keys::LButton, 0x01, Some(0x110), None),
+    (Keys::RButton, 0x02, Some(0x111), None),
+    (Keys::Cancel, 0x03, Some(223), Some(0xff69)),
+    (Keys::MButton, 0x04, Some(0x112), None),
+    (Keys::XButton1, 0x05, Some(0x113), None),
+    (Keys::XButton2, 0x06, Some(0x114), None),
+    (Keys::Back, 0x08, Some(14), Some(0xff08)),
+    (Keys::Tab, 0x09, Some(15), Some(0xff09)),
+    (Keys::LineFeed, 0x0A, Some(101), Some(0xff0a)),
+    (Keys::Clear, 0x0C, Some(355), Some(0xff0b)),
+    (Keys::Return, 0x0D, Some(28), Some(0xff0d)),
+    (Keys::Pause, 0x13, Some(119), Some(0xff13)),
+    (Keys::CapsLock, 0x14, Some(58), Some(0xffe5)),
+    (Keys::HangulMode, 0x15, Some(122), Some(0xff31)),
+    (Keys::JunjaMode, 0x17, None, Some(0xff38)),
+    (Keys::FinalMode, 0x18, None, None),
+    (Keys::HanjaMode, 0x19, Some(123), Some(0xff34)),
+    (Keys::Escape, 0x1B, Some(1), Some(0xff1b)),
+    (Keys::IMEConvert, 0x1C, Some(92), Some(0xff23)),
+    (Keys::IMENonconvert, 0x1D, Some(94), Some(0xff22)),
+    (Keys::IMEModeChange, 0x1F, None, Some(0xff7e)),
+    (Keys::Space, 0x20, Some(57), Some(0x20)),
+    (Keys::PageUp, 0x21, Some(104), Some(0xff55)),
+    (Keys::PageDown, 0x22, Some(109), Some(0xff56)),
+    (Keys::End, 0x23, Some(107), Some(0xff57)),
+    (Keys::Home, 0x24, Some(102), Some(0xff50)),
+    (Keys::Left, 0x25, Some(105), Some(0xff51)),
+    (Keys::Up, 0x26, Some(103), Some(0xff52)),
+    (Keys::Right, 0x27, Some(106), Some(0xff53)),
+    (Keys::Down, 0x28, Some(108), Some(0xff54)),
+    (Keys::Select, 0x29, Some(353), Some(0xff60)),
+    (Keys::Print, 0x2A, Some(210), None),
+    (Keys::Execute, 0x2B, None, Some(0xff62)),
+    (Keys::PrintScreen, 0x2C, Some(99), Some(0xff61)),
+    (Keys::Insert, 0x2D, Some(110), Some(0xff63)),
+    (Keys::Delete, 0x2E, Some(111), Some(0xffff)),
+    (Keys::Help, 0x2F, Some(138), Some(0xff6a)),
+    (Keys::D0, 0x30, Some(11), Some(0x30)),
+    (Keys::D1, 0x31, Some(2), Some(0x31)),
+    (Keys::D2, 0x32, Some(3), Some(0x32)),
+    (Keys::D3, 0x33, Some(4), Some(0x33)),
+    (Keys::D4, 0x34, Some(5), Some(0x34)),
+    (Keys::D5, 0x35, Some(6), Some(0x35)),
+    (Keys::D6, 0x36, Some(7), Some(0x36)),
+    (Keys::D7, 0x37, Some(8), Some(0x37)),
+    (Keys::D8, 0x38, Some(9), Some(0x38)),
+    (Keys::D9, 0x39, Some(10), Some(0x39)),
+    (Keys::A, 0x41, Some(30), Some(0x61)),
+    (Keys::B, 0x42, Some(48), Some(0x62)),
+    (Keys::C, 0x43, Some(46), Some(0x63)),
+    (Keys::D, 0x44, Some(32), Some(0x64)),
+    (Keys::E, 0x45, Some(18), Some(0x65)),
+    (Keys::F, 0x46, Some(33), Some(0x66)),
+    (Keys::G, 0x47, Some(34), Some(0x67)),
+    (Keys::H, 0x48, Some(35), Some(0x68)),
+    (Keys::I, 0x49, Some(23), Some(0x69)),
+    (Keys::J, 0x4A, Some(36), Some(0x6a)),
+    (Keys::K, 0x4B, Some(37), Some(0x6b)),
+    (Keys::L, 0x4C, Some(38), Some(0x6c)),
+    (Keys::M, 0x4D, Some(50), Some(0x6d)),
+    (Keys::N, 0x4E, Some(49), Some(0x6e)),
+    (Keys::O, 0x4F, Some(24), Some(0x6f)),
+    (Keys::P, 0x50, Some(25), Some(0x70)),
+    (Keys::Q, 0x51, Some(16), Some(0x71)),
+    (Keys::R, 0x52, Some(19), Some(0x72)),
+    (Keys::S, 0x53, Some(31), Some(0x73)),
+    (Keys::T, 0x54, Some(20), Some(0x74)),
+    (Keys::U, 0x55, Some(22), Some(0x75)),
+    (Keys::V, 0x56, Some(47), Some(0x76)),
+    (Keys::W, 0x57, Some(17), Some(0x77)),
+    (Keys::X, 0x58, Some(45), Some(0x78)),
+    (Keys::Y, 0x59, Some(21), Some(0x79)),
+    (Keys::Z, 0x5A, Some(44), Some(0x7a)),
+    (Keys::LWin, 0x5B, Some(125), Some(0xffeb)),
+    (Keys::RWin, 0x5C, Some(126), Some(0xffec)),
+    (Keys::Apps, 0x5D, Some(127), Some(0xff67)),
+    (Keys::Sleep, 0x5F, Some(142), Some(0x1008ff2f)),
+    (Keys::NumPad0, 0x60, Some(82), Some(0xffb0)),
+    (Keys::NumPad1, 0x61, Some(79), Some(0xffb1)),
+    (Keys::NumPad2, 0x62, Some(80), Some(0xffb2)),
+    (Keys::NumPad3, 0x63, Some(81), Some(0xffb3)),
+    (Keys::NumPad4, 0x64, Some(75), Some(0xffb4)),
+    (Keys::NumPad5, 0x65, Some(76), Some(0xffb5)),
+    (Keys::NumPad6, 0x66, Some(77), Some(0xffb6)),
+    (Keys::NumPad7, 0x67, Some(71), Some(0xffb7)),
+    (Keys::NumPad8, 0x68, Some(72), Some(0xffb8)),
+    (Keys::NumPad9, 0x69, Some(73), Some(0xffb9)),
+    (Keys::Multiply, 0x6A, Some(55), Some(0xffaa)),
+    (Keys::Add, 0x6B, Some(78), Some(0xffab)),
+    (Keys::Separator, 0x6C, Some(121), Some(0xffac)),
+    (Keys::Subtract, 0x6D, Some(74), Some(0xffad)),
+    (Keys::Decimal, 0x6E, Some(83), Some(0xffae)),
+    (Keys::Divide, 0x6F, Some(98), Some(0xffaf)),
+    (Keys::F1, 0x70, Some(59), Some(0xffbe)),
+    (Keys::F2, 0x71, Some(60), Some(0xffbf)),
+    (Keys::F3, 0x72, Some(61), Some(0xffc0)),
+    (Keys::F4, 0x73, Some(62), Some(0xffc1)),
+    (Keys::F5, 0x74, Some(63), Some(0xffc2)),
+    (Keys::F6, 0x75, Some(64), Some(0xffc3)),
+    (Keys::F7, 0x76, Some(65), Some(0xffc4)),
+    (Keys::F8, 0x77, Some(66), Some(0xffc5)),
+    (Keys::F9, 0x78, Some(67), Some(0xffc6)),
+    (Keys::F10, 0x79, Some(68), Some(0xffc7)),
+    (Keys::F11, 0x7A, Some(87), Some(0xffc8)),
+    (Keys::F12, 0x7B, Some(88), Some(0xffc9)),
+    (Keys::F13, 0x7C, Some(183), Some(0xffca)),
+    (Keys::F14, 0x7D, Some(184), Some(0xffcb)),
+    (Keys::F15, 0x7E, Some(185), Some(0xffcc)),
+    (Keys::F16, 0x7F, Some(186), Some(0xffcd)),
+    (Keys::F17, 0x80, Some(187), Some(0xffce)),
+    (Keys::F18, 0x81, Some(188), Some(0xffcf)),
+    (Keys::F19, 0x82, Some(189), Some(0xffd0)),
+    (Keys::F20, 0x83, Some(190), Some(0xffd1)),
+    (Keys::F21, 0x84, Some(191), Some(0xffd2)),
+    (Keys::F22, 0x85, Some(192), Some(0xffd3)),
+    (Keys::F23, 0x86, Some(193), Some(0xffd4)),
+    (Keys::F24, 0x87, Some(194), Some(0xffd5)),
+    (Keys::NumLock, 0x90, Some(69), Some(0xff7f)),
+    (Keys::Scroll, 0x91, Some(70), Some(0xff14)),
+    (Keys::LShiftKey, 0xA0, Some(42), Some(0xffe1)),
+    (Keys::RShiftKey, 0xA1, Some(54), Some(0xffe2)),
+    (Keys::LControlKey, 0xA2, Some(29), Some(0xffe3)),
+    (Keys::RControlKey, 0xA3, Some(97), Some(0xffe4)),
+    (Keys::LMenu, 0xA4, Some(56), Some(0xffe9)),
+    (Keys::RMenu, 0xA5, Some(100), Some(0xffea)),
+    (Keys::ShiftKey, 0x10, Some(42), Some(0xffe1)),
+    (Keys::ControlKey, 0x11, Some(29), Some(0xffe3)),
+    (Keys::Menu, 0x12, Some(56), Some(0xffe9)),
+    (Keys::BrowserBack, 0xA6, Some(158), Some(0x1008ff26)),
+    (Keys::BrowserForward, 0xA7, Some(159), Some(0x1008ff27)),
+    (Keys::BrowserRefresh, 0xA8, Some(173), Some(0x1008ff29)),
+    (Keys::BrowserStop, 0xA9, Some(128), Some(0x1008ff28)),
+    (Keys::BrowserSearch, 0xAA, Some(217), Some(0x1008ff1b)),
+    (Keys::BrowserFavorites, 0xAB, Some(156), Some(0x1008ff30)),
+    (Keys::BrowserHome, 0xAC, Some(172), Some(0x1008ff18)),
+    (Keys::VolumeMute, 0xAD, Some(113), Some(0x1008ff12)),
+    (Keys::VolumeDown, 0xAE, Some(114), Some(0x1008ff11)),
+    (Keys::VolumeUp, 0xAF, Some(115), Some(0x1008ff13)),
+    (Keys::MediaNextTrack, 0xB0, Some(163), Some(0x1008ff17)),
+    (Keys::MediaPreviousTrack, 0xB1, Some(165), Some(0x1008ff16)),
+    (Keys::MediaStop, 0xB2, Some(166), Some(0x1008ff15)),
+    (Keys::MediaPlayPause, 0xB3, Some(164), Some(0x1008ff14)),
+    (Keys::LaunchMail, 0xB4, Some(155), Some(0x1008ff19)),
+    (Keys::SelectMedia, 0xB5, Some(226), Some(0x1008ff32)),
+    (Keys::LaunchApplication1, 0xB6, Some(157), Some(0x1008ff33)),
+    (Keys::LaunchApplication2, 0xB7, Some(140), Some(0x1008ff1d)),
+    (Keys::OemSemicolon, 0xBA, Some(39), Some(0x3b)),
+    (Keys::Oemplus, 0xBB, Some(13), Some(0x3d)),
+    (Keys::Oemcomma, 0xBC, Some(51), Some(0x2c)),
+    (Keys::OemMinus, 0xBD, Some(12), Some(0x2d)),
+    (Keys::OemPeriod, 0xBE, Some(52), Some(0x2e)),
+    (Keys::OemQuestion, 0xBF, Some(53), Some(0x2f)),
+    (Keys::Oemtilde, 0xC0, Some(41), Some(0x60)),
+    (Keys::OemOpenBrackets, 0xDB, Some(26), Some(0x5b)),
+    (Keys::OemPipe, 0xDC, Some(43), Some(0x5c)),
+    (Keys::OemCloseBrackets, 0xDD, Some(27), Some(0x5d)),
+    (Keys::OemQuotes, 0xDE, Some(40), Some(0x27)),
+    (Keys::Oem8, 0xDF, None, None),
+    (Keys::OemBackslash, 0xE2, Some(86), Some(0x3c)),
+    (Keys::ProcessKey, 0xE5, None, None),
+    (Keys::Packet, 0xE7, None, None),
+    (Keys::Attn, 0xF6, None, Some(0xfd0e)),
+    (Keys::Crsel, 0xF7, None, Some(0xfd1c)),
+    (Keys::Exsel, 0xF8, None, Some(0xfd1d)),
+    (Keys::EraseEof, 0xF9, None, Some(0xfd06)),
+    (Keys::Play, 0xFA, Some(207), Some(0xfd16)),
+    (Keys::Zoom, 0xFB, Some(372), Some(0x1008ff8b)),
+    (Keys::NoName, 0xFC, None, None),
+    (Keys::Pa1, 0xFD, None, None),
+    (Keys::OemClear, 0xFE, None, None),
+];
+
+impl Keys {
+    fn find(predicate: impl Fn(&(Keys, u32, Option<u16>, Option<u32>)) -> bool) -> Option<Self> {
+        CODES
+            .iter()
+            .find(|codes| predicate(codes))
+            .map(|codes| codes.0)
+    }
+
+    fn codes(self) -> &'static (Keys, u32, Option<u16>, Option<u32>) {
+        CODES.iter().find(|codes| codes.0 == self).unwrap()
+    }
+
+    /// Key of a Windows virtual-key code.
+    pub fn from_vk_code(vk_code: u32) -> Option<Self> {
+        Self::find(|codes| codes.1 == vk_code)
+    }
+
+    /// Key of a Linux input event code.
+    pub fn from_evdev_code(evdev_code: u16) -> Option<Self> {
+        Self::find(|codes| codes.2 == Some(evdev_code))
+    }
+
+    /// Key of an X11 keysym.
+    pub fn from_keysym(keysym: u32) -> Option<Self> {
+        Self::find(|codes| codes.3 == Some(keysym))
+    }
+
+    /// Windows virtual-key code, every named key has one.
+    pub fn vk_code(self) -> u32 {
+        self.codes().1
+    }
+
+    /// Linux input event code, if the key has one.
+    pub fn evdev_code(self) -> Option<u16> {
+        self.codes().2
+    }
+
+    /// X11 keysym, if the key has one.
+    pub fn keysym(self) -> Option<u32> {
+        self.codes().3
+    }
+
+    /// Evdev codes of all keys that have one.
+    pub fn evdev_codes() -> impl Iterator<Item = u16> {
+        CODES.iter().filter_map(|codes| codes.2)
+    }
+}
+
+/// Native code of a key that has no name in `Keys`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum KeyCode {
+    /// Windows virtual-key code.
+    Vk(u32),
+    /// Linux input event code.
+    Evdev(u16),
+    /// X11 keysym.
+    Keysym(u32),
+}
+
+/// Platform-neutral key identifier. Codes without a name are kept as they
+/// were read, so they convert back to the same native code.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Key {
+    Named(Keys),
+    Unnamed(KeyCode),
+}
+
+impl Key {
+    /// Names a Windows virtual-key code, keeping it unnamed when unknown.
+    pub fn from_vk_code(vk_code: u32) -> Self {
+        Keys::from_vk_code(vk_code)
+            .map(Key::Named)
+            .unwrap_or(Key::Unnamed(KeyCode::Vk(vk_code)))
+    }
+
+    /// Names a Linux input event code, keeping it unnamed when unknown.
+    pub fn from_evdev_code(evdev_code: u16) -> Self {
+        Keys::from_evdev_code(evdev_code)
+            .map(Key::Named)
+            .unwrap_or(Key::Unnamed(KeyCode::Evdev(evdev_code)))
+    }
+
+    /// Names an X11 keysym, keeping it unnamed when unknown.
+    pub fn from_keysym(keysym: u32) -> Self {
+        Keys::from_keysym(keysym)
+            .map(Key::Named)
+            .unwrap_or(Key::Unnamed(KeyCode::Keysym(keysym)))
+    }
+
+    /// Windows virtual-key code, `None` for an unnamed code of another
+    /// platform.
+    pub fn vk_code(self) -> Option<u32> {
+        match self {
+            Key::Named(key) => Some(key.vk_code()),
+            Key::Unnamed(KeyCode::Vk(vk_code)) => Some(vk_code),
+            Key::Unnamed(_) => None,
+        }
+    }
+
+    /// Linux input event code, `None` for an unnamed code of another
+    /// platform.
+    pub fn evdev_code(self) -> Option<u16> {
+        match self {
+            Key::Named(key) => key.evdev_code(),
+            Key::Unnamed(KeyCode::Evdev(evdev_code)) => Some(evdev_code),
+            Key::Unnamed(_) => None,
+        }
+    }
+
+    /// Whether the key is a mouse button, which only a `MouseHook` reports.
+    pub fn is_mouse_button(self) -> bool {
+        matches!(
+            self,
+            Key::Named(
+                Keys::LButton | Keys::RButton | Keys::MButton | Keys::XButton1 | Keys::XButton2
+            )
+        )
+    }
+
+    /// X11 keysym, `None` for an unnamed code of another platform.
+    pub fn keysym(self) -> Option<u32> {
+        match self {
+            Key::Named(key) => key.keysym(),
+            Key::Unnamed(KeyCode::Keysym(keysym)) => Some(keysym),
+            Key::Unnamed(_) => None,
+        }
+    }
+}
+
+impl From<Keys> for Key {
+    fn from(key: Keys) -> Self {
+        Key::Named(key)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Named(key) => write!(f, "{}", key),
+            Key::Unnamed(KeyCode::Vk(vk_code)) => write!(f, "Vk({:#x})", vk_code),
+            Key::Unnamed(KeyCode::Evdev(evdev_code)) => write!(f, "Evdev({:#x})", evdev_code),
+            Key::Unnamed(KeyCode::Keysym(keysym)) => write!(f, "Keysym({:#x})", keysym),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    /// Parses the `Display` form: a name of `Keys` or a native code such as
+    /// `Vk(0xe8)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(key) = Keys::from_str(s) {
+            return Ok(Key::Named(key));
+        }
+        let parse_code = |prefix: &str| {
+            s.strip_prefix(prefix)
+                .and_then(|s| s.strip_suffix(')'))
+                .and_then(|code| u32::from_str_radix(code.trim_start_matches("0x"), 16).ok())
+        };
+        if let Some(vk_code) = parse_code("Vk(") {
+            Ok(Key::Unnamed(KeyCode::Vk(vk_code)))
+        } else if let Some(evdev_code) = parse_code("Evdev(") {
+            u16::try_from(evdev_code)
+                .map(|evdev_code| Key::Unnamed(KeyCode::Evdev(evdev_code)))
+                .map_err(|_| format!("evdev code out of range in '{}'", s))
+        } else if let Some(keysym) = parse_code("Keysym(") {
+            Ok(Key::Unnamed(KeyCode::Keysym(keysym)))
+        } else {
+            Err(format!("unknown key '{}'", s))
+        }
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unnamed_codes_round_trip() {
+        for key in [
+            Key::Unnamed(KeyCode::Vk(0xe8)),
+            Key::Unnamed(KeyCode::Evdev(0x2ff)),
+            Key::Unnamed(KeyCode::Keysym(0x1008ff13)),
+        ] {
+            assert_eq!(key.to_string().parse::<Key>(), Ok(key));
+        }
+    }
+
+    #[test]
+    fn named_keys_parse_by_name() {
+        assert_eq!("F6".parse::<Key>(), Ok(Key::Named(Keys::F6)));
+        assert!("Nope".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn evdev_codes_out_of_range_are_rejected() {
+        assert!("Evdev(0x12345)".parse::<Key>().is_err());
+    }
+}
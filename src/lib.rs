@@ -0,0 +1,53 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Autoclicker engine: a [`Clicker`] that clicks through a pluggable
+//! [`InputBackend`](input_backend::InputBackend), its [`Settings`] and a
+//! global [`KeyboardHook`] to bind it to hotkeys.
+
+pub mod clicker;
+mod error;
+#[cfg(all(target_os = "linux", feature = "backend-uinput"))]
+mod evdev;
+pub mod hotkey;
+pub mod input_backend;
+pub mod interval;
+pub mod keyboard_hook;
+pub mod keys;
+pub mod scheduler;
+pub mod settings;
+pub mod target;
+// The Xvfb guard of the integration tests, for the unit tests of the X11
+// listener.
+#[cfg(all(test, target_os = "linux", feature = "backend-x11"))]
+#[allow(dead_code)]
+#[path = "../tests/common/mod.rs"]
+mod testing;
+pub mod window;
+#[cfg(all(target_os = "linux", feature = "backend-x11"))]
+mod x11;
+
+pub use clicker::{Clicker, ClickerEvent, ClickerStatus, StopReason};
+pub use error::Error;
+pub use hotkey::{Binding, Hotkey};
+pub use interval::Interval;
+pub use keyboard_hook::{KeyEvent, KeyboardHook, MouseHook};
+pub use keys::{Key, KeyCode, Keys};
+pub use scheduler::Timing;
+pub use settings::{ActivationMode, ClickType, Delivery, HotkeyAction, Rate, Settings};
+pub use target::{Target, TargetOrder};
+pub use window::WindowMatch;
@@ -15,60 +15,33 @@
 *   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-#![windows_subsystem = "windows"]
+#![cfg_attr(feature = "gui-nwg", windows_subsystem = "windows")]
 
-mod clicker;
+#[cfg(not(any(feature = "cli", all(windows, feature = "gui-nwg"))))]
+compile_error!("the `cli` feature or, on Windows, the `gui-nwg` feature is required");
+
+#[cfg(feature = "cli")]
+mod cli;
+#[cfg(all(windows, feature = "gui-nwg"))]
 mod gui;
-mod keyboard_hook;
-mod keys;
+#[cfg(all(windows, feature = "gui-nwg"))]
 mod resources;
-mod settings;
 
-use clicker::{Clicker, ClickerStatus};
-use gui::App;
-use keyboard_hook::KeyboardHook;
-use nwg::NativeUi;
-use settings::Settings;
-use std::sync::Arc;
-use std::sync::Mutex;
+#[cfg(feature = "cli")]
+use std::{env, process};
 
+/// Runs the command-line mode when options are passed or when there is no
+/// GUI to show.
 fn main() {
-    let settings = Arc::new(Mutex::new(Settings::load_or_default()));
-    let clicker = Clicker::new(settings.lock().unwrap().clone());
-    let keyboard_hook = Arc::new(Mutex::new({
-        let settings = settings.clone();
-        let clicker = clicker.clone();
-        KeyboardHook::new(Arc::new(move |vk_code: u32| {
-            if let Some(activation_key_code) = settings.lock().unwrap().activation_key {
-                if vk_code == activation_key_code as u32 {
-                    match clicker.status() {
-                        ClickerStatus::Clicking => clicker.stop(),
-                        ClickerStatus::Idle => clicker.start(),
-                    }
-                }
-            }
-        }))
-    }));
-    keyboard_hook.lock().unwrap().start();
-
-    nwg::init().unwrap();
-    nwg::Font::set_global_family("Segoe UI").unwrap();
-
-    let _app = App::build_ui(App::new(
-        settings.lock().unwrap().clone(),
-        Box::new({
-            let settings = settings.clone();
-            let clicker = clicker.clone();
-            move |changed_settings| {
-                *settings.lock().unwrap() = changed_settings.clone();
-                clicker.set_settings(changed_settings.clone());
-            }
-        }),
-        keyboard_hook.clone(),
-    ))
-    .unwrap();
-
-    nwg::dispatch_thread_events();
-
-    settings.lock().unwrap().save();
+    #[cfg(feature = "cli")]
+    if env::args().len() > 1 || !cfg!(all(windows, feature = "gui-nwg")) {
+        #[cfg(all(windows, feature = "gui-nwg"))]
+        unsafe {
+            winapi::um::wincon::AttachConsole(winapi::um::wincon::ATTACH_PARENT_PROCESS);
+        }
+        process::exit(cli::run(env::args().skip(1)));
+    }
+
+    #[cfg(all(windows, feature = "gui-nwg"))]
+    gui::run();
 }
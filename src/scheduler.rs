@@ -0,0 +1,209 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Timing of the clicks against absolute deadlines.
+
+use std::hint;
+use std::time::{Duration, Instant};
+
+/// Part of a wait that is spent busy-waiting when spinning is enabled, it
+/// covers the usual oversleep of `thread::sleep`.
+pub const SPIN_WINDOW: Duration = Duration::from_millis(1);
+
+/// Measured timing of a run of the clicker.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct Timing {
+    /// Clicks made so far.
+    pub clicks: u64,
+    /// Achieved clicks per second, from the first click to the last one.
+    pub rate: f64,
+    /// Standard deviation of the click lateness.
+    pub jitter: Duration,
+    /// Largest delay of a click after its deadline.
+    pub max_lateness: Duration,
+}
+
+/// Schedules clicks at absolute deadlines. Every deadline is one period after
+/// the previous deadline rather than after the previous click, so that
+/// lateness does not accumulate into drift.
+pub struct Scheduler {
+    deadline: Option<Instant>,
+    first_tick: Option<Instant>,
+    last_tick: Instant,
+    ticks: u64,
+    lateness_sum: f64,
+    lateness_square_sum: f64,
+    max_lateness: Duration,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    /// Creates a scheduler whose first deadline is now.
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            deadline: Some(now),
+            first_tick: None,
+            last_tick: now,
+            ticks: 0,
+            lateness_sum: 0.0,
+            lateness_square_sum: 0.0,
+            max_lateness: Duration::ZERO,
+        }
+    }
+
+    /// When the next event is due, `None` when a period too long for an
+    /// `Instant` pushed it out of reach.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Records that the event due at the current deadline happened at `time`.
+    pub fn tick(&mut self, time: Instant) {
+        let lateness = self.deadline.map_or(Duration::ZERO, |deadline| {
+            time.saturating_duration_since(deadline)
+        });
+        self.first_tick.get_or_insert(time);
+        self.last_tick = time;
+        self.ticks += 1;
+        self.lateness_sum += lateness.as_secs_f64();
+        self.lateness_square_sum += lateness.as_secs_f64().powi(2);
+        self.max_lateness = self.max_lateness.max(lateness);
+    }
+
+    /// Moves the deadline one period on. When the scheduler has fallen more
+    /// than a period behind, the missed deadlines are dropped instead of
+    /// being caught up with a burst of clicks.
+    pub fn advance(&mut self, period: Duration) {
+        self.deadline = self
+            .deadline
+            .and_then(|deadline| deadline.checked_add(period));
+        let now = Instant::now();
+        if self
+            .deadline
+            .and_then(|deadline| deadline.checked_add(period))
+            .is_some_and(|next_deadline| next_deadline < now)
+        {
+            self.deadline = Some(now);
+        }
+    }
+
+    /// Makes the next deadline now, after a pause for example.
+    pub fn reset(&mut self) {
+        self.deadline = Some(Instant::now());
+    }
+
+    /// Timing of the events recorded so far.
+    pub fn timing(&self) -> Timing {
+        let elapsed = match self.first_tick {
+            Some(first_tick) => (self.last_tick - first_tick).as_secs_f64(),
+            None => 0.0,
+        };
+        let (mean, mean_square) = match self.ticks {
+            0 => (0.0, 0.0),
+            ticks => (
+                self.lateness_sum / ticks as f64,
+                self.lateness_square_sum / ticks as f64,
+            ),
+        };
+        Timing {
+            clicks: self.ticks,
+            rate: if elapsed > 0.0 {
+                (self.ticks - 1) as f64 / elapsed
+            } else {
+                0.0
+            },
+            jitter: Duration::from_secs_f64((mean_square - mean * mean).max(0.0).sqrt()),
+            max_lateness: self.max_lateness,
+        }
+    }
+}
+
+/// Busy-waits until `instant`.
+pub fn spin_until(instant: Instant) {
+    while Instant::now() < instant {
+        hint::spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn ms(milliseconds: u64) -> Duration {
+        Duration::from_millis(milliseconds)
+    }
+
+    #[test]
+    fn advance_adds_the_period_to_the_deadline() {
+        let mut scheduler = Scheduler::new();
+        let start = scheduler.deadline().unwrap();
+        scheduler.advance(Duration::from_secs(60));
+        scheduler.advance(Duration::from_secs(60));
+        assert_eq!(scheduler.deadline(), Some(start + Duration::from_secs(120)));
+        scheduler.advance(Duration::MAX);
+        assert_eq!(scheduler.deadline(), None);
+    }
+
+    #[test]
+    fn advance_drops_missed_deadlines() {
+        let mut scheduler = Scheduler::new();
+        let start = scheduler.deadline().unwrap();
+        thread::sleep(ms(50));
+        let before = Instant::now();
+        scheduler.advance(ms(10));
+        // Instead of the missed deadlines at 10, 20, 30 and 40 ms.
+        assert!(scheduler.deadline().unwrap() >= before);
+        scheduler.advance(ms(10));
+        assert!(scheduler.deadline().unwrap() >= before + ms(10));
+        assert!(scheduler.deadline().unwrap() > start + ms(50));
+    }
+
+    #[test]
+    fn reset_makes_the_next_deadline_now() {
+        let mut scheduler = Scheduler::new();
+        scheduler.advance(Duration::from_secs(60));
+        let before = Instant::now();
+        scheduler.reset();
+        let deadline = scheduler.deadline().unwrap();
+        assert!(before <= deadline && deadline <= Instant::now());
+    }
+
+    #[test]
+    fn timing_measures_rate_and_lateness() {
+        let mut scheduler = Scheduler::new();
+        assert_eq!(scheduler.timing(), Timing::default());
+        let start = scheduler.deadline().unwrap();
+        // Late by 0, 2 and 4 ms at deadlines 10 ms apart.
+        for (click, lateness) in [0, 2, 4].iter().enumerate() {
+            scheduler.tick(start + ms(10 * click as u64 + lateness));
+            scheduler.advance(ms(10));
+        }
+        let timing = scheduler.timing();
+        assert_eq!(timing.clicks, 3);
+        assert!((timing.rate - 2.0 / 0.024).abs() < 1e-6);
+        assert_eq!(timing.max_lateness, ms(4));
+        let jitter = timing.jitter.as_secs_f64() * 1000.0;
+        assert!((jitter - (8.0f64 / 3.0).sqrt()).abs() < 1e-6);
+    }
+}
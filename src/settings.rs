@@ -15,27 +15,43 @@
 *   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::keys::Keys;
+//! Configuration of the clicker and its storage.
+
+use crate::error::Error;
+use crate::hotkey::{Binding, Hotkey};
+use crate::interval::Interval;
+use crate::keyboard_hook::KeyEvent;
+use crate::target::{Target, TargetOrder};
+use crate::window::WindowMatch;
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::fs::File;
-use std::io::Read;
+use std::fs;
 use std::path::PathBuf;
-use std::{io::Write, time::Duration};
-use strum::EnumIter;
+use std::time::Duration;
+use strum::{EnumIter, EnumString, IntoEnumIterator};
 
-#[derive(PartialEq, EnumIter, Copy, Clone, Deserialize, Serialize)]
+/// What the clicker does on each click. Parsing ignores case.
+#[derive(PartialEq, EnumIter, EnumString, Copy, Clone, Default, Deserialize, Serialize)]
+#[strum(ascii_case_insensitive)]
 pub enum ClickType {
+    #[default]
     Left,
     Right,
     Middle,
-}
-
-impl Default for ClickType {
-    fn default() -> Self {
-        ClickType::Left
-    }
+    /// First side button.
+    X1,
+    /// Second side button.
+    X2,
+    /// Two left clicks within the double-click interval of the system.
+    Double,
+    /// Three left clicks within the double-click interval of the system.
+    Triple,
+    /// `Settings::wheel_delta` ticks of the vertical wheel.
+    Wheel,
+    /// `Settings::wheel_delta` ticks of the horizontal wheel.
+    #[strum(serialize = "HorizontalWheel", serialize = "horizontal-wheel")]
+    HorizontalWheel,
 }
 
 impl fmt::Display for ClickType {
@@ -47,69 +63,379 @@ impl fmt::Display for ClickType {
                 ClickType::Left => "Left",
                 ClickType::Right => "Right",
                 ClickType::Middle => "Middle",
+                ClickType::X1 => "X1",
+                ClickType::X2 => "X2",
+                ClickType::Double => "Double",
+                ClickType::Triple => "Triple",
+                ClickType::Wheel => "Wheel",
+                ClickType::HorizontalWheel => "Horizontal wheel",
+            }
+        )
+    }
+}
+
+/// How the clicks reach their target. Parsing ignores case.
+#[derive(
+    PartialEq, Eq, EnumIter, EnumString, Copy, Clone, Debug, Default, Deserialize, Serialize,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum Delivery {
+    /// Injected like physical input, at the pointer.
+    #[default]
+    Global,
+    /// Posted to `Settings::target_window` at client coordinates, without
+    /// moving the pointer or focusing the window.
+    Window,
+}
+
+impl fmt::Display for Delivery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Delivery::Global => "Global",
+                Delivery::Window => "Post to window",
             }
         )
     }
 }
 
+/// What the activation key does.
+#[derive(
+    PartialEq, Eq, EnumIter, EnumString, Copy, Clone, Debug, Default, Deserialize, Serialize,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum ActivationMode {
+    /// Each press starts or stops the clicker.
+    #[default]
+    Toggle,
+    /// The clicker runs while the key is held down.
+    Hold,
+    /// Each press starts a run that ends at the click or time limit, or
+    /// after a single click when there is no limit.
+    #[strum(serialize = "OneShot", serialize = "one-shot")]
+    OneShot,
+}
+
+impl fmt::Display for ActivationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ActivationMode::Toggle => "Toggle",
+                ActivationMode::Hold => "Hold",
+                ActivationMode::OneShot => "One-shot",
+            }
+        )
+    }
+}
+
+/// Operation of the clicker that a hotkey is bound to.
+#[derive(PartialEq, Eq, Hash, EnumIter, Copy, Clone, Debug)]
+pub enum HotkeyAction {
+    /// Controls the clicker according to `Settings::activation_mode`.
+    Activate,
+    Start,
+    Stop,
+    /// Pauses a running clicker or resumes a paused one.
+    Pause,
+    /// Stops every clicker, whatever state it is in.
+    Panic,
+}
+
+impl fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                HotkeyAction::Activate => "Activation",
+                HotkeyAction::Start => "Start",
+                HotkeyAction::Stop => "Stop",
+                HotkeyAction::Pause => "Pause",
+                HotkeyAction::Panic => "Panic",
+            }
+        )
+    }
+}
+
+/// Most wheel ticks of one click either way. Posted wheel messages of
+/// Windows hold the distance in 16 bits, at 120 per tick.
+pub const MAX_WHEEL_DELTA: i32 = 255;
+
+/// Period used for a click rate of zero or less, which in practice never
+/// clicks again.
+const SLOWEST_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How often the clicker clicks, either as a number of clicks per second or
+/// as the time from the start of one click to the start of the next.
+#[derive(PartialEq, Copy, Clone, Debug, Deserialize, Serialize)]
+// Untagged so that a period is written like an `Interval`, a plain number is
+// a rate in clicks per second.
+#[serde(untagged)]
+pub enum Rate {
+    /// Average number of clicks per second, at a fixed period.
+    ClicksPerSecond(f64),
+    /// Time from the start of a click to the start of the next one.
+    Period(Interval),
+}
+
+impl Rate {
+    /// Period of the rate, a distribution for a period and a fixed length for
+    /// clicks per second.
+    pub fn period(&self) -> Interval {
+        match *self {
+            Rate::ClicksPerSecond(clicks_per_second) => Interval::Fixed(
+                Duration::try_from_secs_f64(clicks_per_second.recip())
+                    .map_or(SLOWEST_PERIOD, |period| period.min(SLOWEST_PERIOD)),
+            ),
+            Rate::Period(period) => period,
+        }
+    }
+
+    /// Average number of clicks per second.
+    pub fn clicks_per_second(&self) -> f64 {
+        match *self {
+            Rate::ClicksPerSecond(clicks_per_second) => clicks_per_second,
+            Rate::Period(period) => period.mean().as_secs_f64().recip(),
+        }
+    }
+}
+
+/// Configuration of the clicker, stored as JSON in the user's configuration
+/// directory.
 #[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Settings {
-    pub activation_key: Option<Keys>,
-    pub click_delay: Duration,
-    pub click_duration: Duration,
+    /// Hotkey that controls the clicker.
+    pub activation_key: Option<Binding>,
+    /// What the activation key does.
+    pub activation_mode: ActivationMode,
+    /// Hotkey that starts the clicker.
+    pub start_key: Option<Binding>,
+    /// Hotkey that stops the clicker.
+    pub stop_key: Option<Binding>,
+    /// Hotkey that pauses and resumes the clicker.
+    pub pause_key: Option<Binding>,
+    /// Hotkey that stops every clicker.
+    pub panic_key: Option<Binding>,
+    /// Ignore the hotkeys when they are injected by software, such as macro
+    /// tools, instead of typed on a physical keyboard.
+    pub physical_keys_only: bool,
+    /// Rate of the clicks.
+    pub click_rate: Rate,
+    /// Time the button is held down for.
+    pub click_duration: Interval,
+    /// What the clicker does on each click.
     pub click_type: ClickType,
+    /// Wheel ticks of the wheel click types, positive values scroll up or
+    /// right. Limited to `MAX_WHEEL_DELTA` either way.
+    pub wheel_delta: i32,
+    /// Points that the clicker clicks at, where the pointer is when empty.
+    pub targets: Vec<Target>,
+    /// Order in which the targets are clicked.
+    pub target_order: TargetOrder,
+    /// Window whose client area the targets are relative to. The clicker
+    /// pauses while no window matches.
+    pub target_window: Option<WindowMatch>,
+    /// Window that must have the focus for the clicker to click. The clicker
+    /// pauses while another window has it.
+    pub focus_window: Option<WindowMatch>,
+    /// Has no effect without `target_window`.
+    pub delivery: Delivery,
+    /// Move the pointer back to where it was when the clicker stops
+    /// clicking at targets. Posted clicks never move it.
+    pub restore_cursor: bool,
+    /// Number of clicks after which the clicker stops.
+    pub click_limit: Option<u64>,
+    /// Time after which the clicker stops, counted from its start.
+    pub time_limit: Option<Duration>,
+    /// Seed for the random intervals, so that a run can be repeated exactly.
+    /// A new seed is picked for every run when it is `None`.
+    pub random_seed: Option<u64>,
+    /// Busy-wait the end of each delay to click on time, at the cost of CPU
+    /// time.
+    pub spin_wait: bool,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Settings {
             activation_key: None,
-            click_delay: Duration::from_millis(100),
-            click_duration: Duration::from_millis(0),
+            activation_mode: ActivationMode::Toggle,
+            start_key: None,
+            stop_key: None,
+            pause_key: None,
+            panic_key: None,
+            physical_keys_only: false,
+            click_rate: Rate::Period(Duration::from_millis(100).into()),
+            click_duration: Duration::from_millis(0).into(),
             click_type: ClickType::Left,
+            wheel_delta: 1,
+            targets: Vec::new(),
+            target_order: TargetOrder::Sequential,
+            target_window: None,
+            focus_window: None,
+            delivery: Delivery::Global,
+            restore_cursor: false,
+            click_limit: None,
+            time_limit: None,
+            random_seed: None,
+            spin_wait: false,
         }
     }
 }
 
 impl Settings {
-    fn get_config_directory_path() -> PathBuf {
-        ProjectDirs::from("com.github", "trickybestia", "rsautoclicker")
-            .unwrap()
-            .config_dir()
-            .to_path_buf()
+    /// Binding of `action`, `None` when it has no hotkey.
+    pub fn binding(&self, action: HotkeyAction) -> Option<Binding> {
+        match action {
+            HotkeyAction::Activate => self.activation_key,
+            HotkeyAction::Start => self.start_key,
+            HotkeyAction::Stop => self.stop_key,
+            HotkeyAction::Pause => self.pause_key,
+            HotkeyAction::Panic => self.panic_key,
+        }
+    }
+
+    /// Binding of `action`, to change it.
+    pub fn binding_mut(&mut self, action: HotkeyAction) -> &mut Option<Binding> {
+        match action {
+            HotkeyAction::Activate => &mut self.activation_key,
+            HotkeyAction::Start => &mut self.start_key,
+            HotkeyAction::Stop => &mut self.stop_key,
+            HotkeyAction::Pause => &mut self.pause_key,
+            HotkeyAction::Panic => &mut self.panic_key,
+        }
+    }
+
+    /// Whether `event` matches the binding of `action`, taking
+    /// `physical_keys_only` into account.
+    pub fn hotkey_matches(&self, action: HotkeyAction, event: &KeyEvent) -> bool {
+        !(event.is_injected && self.physical_keys_only)
+            && self
+                .binding(action)
+                .is_some_and(|binding| binding.matches(event))
     }
 
-    fn get_config_file_path() -> PathBuf {
-        Self::get_config_directory_path().join("config.json")
+    /// Hotkeys of the bindings whose key events are hidden from the other
+    /// applications.
+    pub fn consumed_hotkeys(&self) -> Vec<Hotkey> {
+        HotkeyAction::iter()
+            .filter_map(|action| self.binding(action))
+            .filter(|binding| binding.consume)
+            .map(|binding| binding.hotkey)
+            .collect()
     }
 
-    pub fn save(&self) {
-        let config_file_path_buf = Self::get_config_file_path();
-        std::fs::create_dir_all(Self::get_config_directory_path().as_path()).unwrap();
-        File::create(config_file_path_buf.as_path())
-            .unwrap()
-            .write_all(serde_json::to_string(self).unwrap().as_bytes())
-            .unwrap();
+    fn get_config_directory_path() -> Result<PathBuf, Error> {
+        Ok(
+            ProjectDirs::from("com.github", "trickybestia", "rsautoclicker")
+                .ok_or(Error::NoConfigDirectory)?
+                .config_dir()
+                .to_path_buf(),
+        )
+    }
+
+    fn get_config_file_path() -> Result<PathBuf, Error> {
+        Ok(Self::get_config_directory_path()?.join("config.json"))
+    }
+
+    /// Writes the settings to the configuration file.
+    pub fn save(&self) -> Result<(), Error> {
+        fs::create_dir_all(Self::get_config_directory_path()?).map_err(Error::Io)?;
+        fs::write(
+            Self::get_config_file_path()?,
+            serde_json::to_string(self).map_err(Error::Serialization)?,
+        )
+        .map_err(Error::Io)
     }
 
+    /// Reads the configuration file, falling back to the defaults when it is
+    /// missing or invalid.
     pub fn load_or_default() -> Self {
-        if let Ok(settings) = Self::load() {
-            settings
-        } else {
-            Default::default()
-        }
+        Self::load().unwrap_or_default()
     }
 
-    pub fn load() -> Result<Self, ()> {
-        let path_buf = Self::get_config_file_path();
-        if let Ok(mut file) = File::open(path_buf.as_path()) {
-            let mut buffer = String::new();
-            if let Ok(_) = file.read_to_string(&mut buffer) {
-                if let Ok(settings) = serde_json::from_str(buffer.as_str()) {
-                    return Ok(settings);
+    /// Reads the configuration file.
+    pub fn load() -> Result<Self, Error> {
+        Self::from_json(&fs::read_to_string(Self::get_config_file_path()?).map_err(Error::Io)?)
+    }
+
+    /// Parses settings in the form written by `save` or by older versions.
+    /// Their `click_delay` was the pause after each click, it becomes the
+    /// period from the start of one click to the start of the next.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let mut value: serde_json::Value =
+            serde_json::from_str(json).map_err(Error::Serialization)?;
+        if let Some(object) = value.as_object_mut() {
+            if let Some(click_delay) = object.remove("click_delay") {
+                if !object.contains_key("click_rate") {
+                    let click_delay: Interval =
+                        serde_json::from_value(click_delay).map_err(Error::Serialization)?;
+                    let click_duration = match object.get("click_duration") {
+                        Some(click_duration) => {
+                            serde_json::from_value::<Interval>(click_duration.clone())
+                                .map_err(Error::Serialization)?
+                                .mean()
+                        }
+                        None => Duration::ZERO,
+                    };
+                    object.insert(
+                        "click_rate".to_owned(),
+                        serde_json::to_value(Rate::Period(click_delay.shifted(click_duration)))
+                            .map_err(Error::Serialization)?,
+                    );
                 }
             }
         }
-        Err(())
+        serde_json::from_value(value).map_err(Error::Serialization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::Keys;
+
+    #[test]
+    fn click_delay_of_older_versions_includes_the_click_duration() {
+        let settings = Settings::from_json(
+            r#"{"click_delay":{"secs":0,"nanos":100000000},"click_duration":{"secs":0,"nanos":20000000}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.click_rate,
+            Rate::Period(Duration::from_millis(120).into())
+        );
+    }
+
+    #[test]
+    fn click_rate_wins_over_click_delay() {
+        let settings =
+            Settings::from_json(r#"{"click_rate":5.0,"click_delay":{"secs":1,"nanos":0}}"#)
+                .unwrap();
+        assert_eq!(settings.click_rate, Rate::ClicksPerSecond(5.0));
+    }
+
+    #[test]
+    fn loads_the_config_of_the_first_version() {
+        let settings = Settings::from_json(
+            r#"{"activation_key":"F6","click_delay":{"secs":0,"nanos":100000000},"click_duration":{"secs":0,"nanos":0},"click_type":"Left"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.activation_key,
+            Some(Binding::from(Hotkey::from(Keys::F6)))
+        );
+        assert_eq!(
+            settings.click_rate,
+            Rate::Period(Duration::from_millis(100).into())
+        );
+        assert_eq!(settings.click_duration, Interval::Fixed(Duration::ZERO));
+        assert!(settings.click_type == ClickType::Left);
+        assert_eq!(settings.activation_mode, ActivationMode::Toggle);
     }
 }
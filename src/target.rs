@@ -0,0 +1,246 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Points of the screen that the clicker clicks at.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use strum::{EnumIter, EnumString};
+
+/// Point of the screen that the clicker moves the pointer to before clicking.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Deserialize, Serialize)]
+pub struct Target {
+    pub x: i32,
+    pub y: i32,
+    /// Number of clicks made at the point before moving on to the next one.
+    #[serde(default = "default_repeat")]
+    pub repeat: u32,
+}
+
+fn default_repeat() -> u32 {
+    1
+}
+
+impl Target {
+    /// Point that is clicked once in a row.
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y, repeat: 1 }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)?;
+        if self.repeat != 1 {
+            write!(f, "*{}", self.repeat)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    /// Parses `X,Y`, optionally followed by `*N` to click the point N times.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid target '{}'", text);
+        let (point, repeat) = match text.split_once('*') {
+            Some((point, repeat)) => (point, repeat.trim().parse().map_err(|_| invalid())?),
+            None => (text, 1),
+        };
+        let (x, y) = point.split_once(',').ok_or_else(invalid)?;
+        Ok(Self {
+            x: x.trim().parse().map_err(|_| invalid())?,
+            y: y.trim().parse().map_err(|_| invalid())?,
+            repeat,
+        })
+    }
+}
+
+/// Parses a list of targets separated by `;`, such as `100,200*3; 300,400`.
+pub fn parse_targets(text: &str) -> Result<Vec<Target>, String> {
+    text.split(';')
+        .map(str::trim)
+        .filter(|target| !target.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Formats targets in the form read by `parse_targets`.
+pub fn format_targets(targets: &[Target]) -> String {
+    targets
+        .iter()
+        .map(Target::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Order in which the clicker moves through its targets. Parsing ignores
+/// case.
+#[derive(
+    PartialEq, Eq, EnumIter, EnumString, Copy, Clone, Debug, Default, Deserialize, Serialize,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum TargetOrder {
+    /// From the first target to the last one and then from the first again.
+    #[default]
+    Sequential,
+    /// Forwards and backwards, without clicking the ends twice in a row.
+    #[strum(serialize = "PingPong", serialize = "ping-pong")]
+    PingPong,
+    /// A randomly picked target each time.
+    Random,
+}
+
+impl fmt::Display for TargetOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TargetOrder::Sequential => "Sequential",
+                TargetOrder::PingPong => "Ping-pong",
+                TargetOrder::Random => "Random",
+            }
+        )
+    }
+}
+
+/// Position of a run in the list of targets.
+#[derive(Default, Copy, Clone)]
+pub(crate) struct TargetCycle {
+    index: usize,
+    /// Clicks made at the current target, zero before the first click.
+    clicks: u32,
+    is_backwards: bool,
+}
+
+impl TargetCycle {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Target of the next click, `None` when there are no targets. The list
+    /// may change between calls.
+    pub fn next(
+        &mut self,
+        targets: &[Target],
+        order: TargetOrder,
+        rng: &mut impl Rng,
+    ) -> Option<Target> {
+        if targets.is_empty() {
+            return None;
+        }
+        if self.clicks == 0 && order == TargetOrder::Random {
+            self.index = rng.gen_range(0..targets.len());
+        }
+        if self.index >= targets.len() {
+            self.index = 0;
+            self.clicks = 0;
+        }
+        if self.clicks >= targets[self.index].repeat.max(1) {
+            self.index = self.advance(targets.len(), order, rng);
+            self.clicks = 0;
+        }
+        self.clicks += 1;
+        Some(targets[self.index])
+    }
+
+    fn advance(&mut self, count: usize, order: TargetOrder, rng: &mut impl Rng) -> usize {
+        match order {
+            TargetOrder::Sequential => (self.index + 1) % count,
+            TargetOrder::PingPong if count == 1 => 0,
+            TargetOrder::PingPong => {
+                if self.index == 0 {
+                    self.is_backwards = false;
+                } else if self.index + 1 == count {
+                    self.is_backwards = true;
+                }
+                if self.is_backwards {
+                    self.index - 1
+                } else {
+                    self.index + 1
+                }
+            }
+            TargetOrder::Random => rng.gen_range(0..count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn parses_targets_with_repeats() {
+        assert_eq!(
+            parse_targets("100,200*3; 300, 400;"),
+            Ok(vec![
+                Target {
+                    x: 100,
+                    y: 200,
+                    repeat: 3
+                },
+                Target::new(300, 400),
+            ])
+        );
+        assert!(parse_targets("100").is_err());
+        assert!(parse_targets("100,200*x").is_err());
+    }
+
+    #[test]
+    fn format_round_trips() {
+        let targets = parse_targets("-5,7*2; 1,1").unwrap();
+        assert_eq!(parse_targets(&format_targets(&targets)), Ok(targets));
+    }
+
+    #[test]
+    fn ping_pong_does_not_repeat_the_ends() {
+        let targets: Vec<_> = (0..3).map(|x| Target::new(x, 0)).collect();
+        let mut cycle = TargetCycle::new();
+        let mut rng = StdRng::seed_from_u64(0);
+        let order: Vec<_> = (0..8)
+            .map(|_| {
+                cycle
+                    .next(&targets, TargetOrder::PingPong, &mut rng)
+                    .unwrap()
+                    .x
+            })
+            .collect();
+        assert_eq!(order, [0, 1, 2, 1, 0, 1, 2, 1]);
+    }
+
+    #[test]
+    fn repeats_click_a_target_several_times_in_a_row() {
+        let targets = parse_targets("0,0*2; 1,0").unwrap();
+        let mut cycle = TargetCycle::new();
+        let mut rng = StdRng::seed_from_u64(0);
+        let order: Vec<_> = (0..6)
+            .map(|_| {
+                cycle
+                    .next(&targets, TargetOrder::Sequential, &mut rng)
+                    .unwrap()
+                    .x
+            })
+            .collect();
+        assert_eq!(order, [0, 0, 1, 0, 0, 1]);
+    }
+}
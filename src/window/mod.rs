@@ -0,0 +1,201 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Lookup of the top-level windows of the desktop, so that the clicker can
+//! follow a window that moves.
+
+#[cfg(all(windows, feature = "backend-win32"))]
+mod win32;
+#[cfg(all(target_os = "linux", feature = "backend-x11"))]
+mod x11;
+
+use crate::input_backend::{MouseButton, ScrollAxis};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// Top-level window found on the desktop.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct WindowInfo {
+    /// `HWND` on Windows, X11 window id on Linux.
+    pub id: u64,
+    pub title: String,
+    pub class: String,
+    /// File name of the executable that owns the window.
+    pub process: String,
+}
+
+/// Rule that picks a window by its title, class or process, the criteria
+/// that are set must all match. Comparisons ignore case and the title only
+/// has to contain the given text.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WindowMatch {
+    pub title: Option<String>,
+    pub class: Option<String>,
+    /// Executable name, `.exe` may be left out.
+    pub process: Option<String>,
+}
+
+impl WindowMatch {
+    /// Whether `window` meets every criterion that is set.
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        let title = window.title.to_lowercase();
+        self.title
+            .as_ref()
+            .is_none_or(|text| title.contains(&text.to_lowercase()))
+            && self
+                .class
+                .as_ref()
+                .is_none_or(|class| class.eq_ignore_ascii_case(&window.class))
+            && self.process.as_ref().is_none_or(|process| {
+                process.eq_ignore_ascii_case(&window.process)
+                    || window
+                        .process
+                        .rsplit_once('.')
+                        .is_some_and(|(stem, _)| process.eq_ignore_ascii_case(stem))
+            })
+    }
+}
+
+impl fmt::Display for WindowMatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let criteria: Vec<_> = [
+            ("title", &self.title),
+            ("class", &self.class),
+            ("process", &self.process),
+        ]
+        .iter()
+        .filter_map(|(name, value)| value.as_ref().map(|value| format!("{}:{}", name, value)))
+        .collect();
+        write!(f, "{}", criteria.join("; "))
+    }
+}
+
+impl FromStr for WindowMatch {
+    type Err = String;
+
+    /// Parses criteria separated by `;` such as `process:game.exe; title:Level`,
+    /// text without a criterion name is a title.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut rule = WindowMatch::default();
+        for criterion in text.split(';').map(str::trim).filter(|c| !c.is_empty()) {
+            let (field, value) = match criterion.split_once(':') {
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("title") => {
+                    (&mut rule.title, value)
+                }
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("class") => {
+                    (&mut rule.class, value)
+                }
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("process") => {
+                    (&mut rule.process, value)
+                }
+                _ => (&mut rule.title, criterion),
+            };
+            *field = Some(value.trim().to_owned());
+        }
+        if rule == WindowMatch::default() {
+            return Err(format!("invalid window '{}'", text));
+        }
+        Ok(rule)
+    }
+}
+
+/// Visible top-level windows, the topmost first where the platform tells.
+#[allow(unreachable_code)]
+pub fn windows() -> io::Result<Vec<WindowInfo>> {
+    #[cfg(all(windows, feature = "backend-win32"))]
+    return win32::windows();
+
+    #[cfg(all(target_os = "linux", feature = "backend-x11"))]
+    return x11::windows();
+
+    Err(unsupported())
+}
+
+/// First window that matches `rule`, `None` when there is none.
+pub fn find(rule: &WindowMatch) -> io::Result<Option<WindowInfo>> {
+    Ok(windows()?.into_iter().find(|window| rule.matches(window)))
+}
+
+/// Window that has the keyboard focus, `None` when there is none or it is
+/// minimized.
+#[allow(unreachable_code)]
+pub fn foreground() -> io::Result<Option<WindowInfo>> {
+    #[cfg(all(windows, feature = "backend-win32"))]
+    return win32::foreground();
+
+    #[cfg(all(target_os = "linux", feature = "backend-x11"))]
+    return x11::foreground();
+
+    Err(unsupported())
+}
+
+/// Screen coordinates of the top left corner of the client area of `window`,
+/// `None` when the window no longer exists or is hidden.
+#[allow(unreachable_code, unused_variables)]
+pub fn client_origin(window: &WindowInfo) -> io::Result<Option<(i32, i32)>> {
+    #[cfg(all(windows, feature = "backend-win32"))]
+    return win32::client_origin(window.id);
+
+    #[cfg(all(target_os = "linux", feature = "backend-x11"))]
+    return x11::client_origin(window.id);
+
+    Err(unsupported())
+}
+
+/// Posts a press or release of `button` to the window `id`, at `x`, `y` in
+/// its client area, without moving the pointer. A press with
+/// `is_double_click` set is the second one of a double click, which Windows
+/// tells apart with its own messages while X11 leaves it to the timing.
+#[allow(unreachable_code, unused_variables)]
+pub(crate) fn post_button(
+    id: u64,
+    button: MouseButton,
+    is_pressed: bool,
+    is_double_click: bool,
+    x: i32,
+    y: i32,
+) -> io::Result<()> {
+    #[cfg(all(windows, feature = "backend-win32"))]
+    return win32::post_button(id, button, is_pressed, is_double_click, x, y);
+
+    #[cfg(all(target_os = "linux", feature = "backend-x11"))]
+    return x11::post_button(id, button, is_pressed, x, y);
+
+    Err(unsupported())
+}
+
+/// Posts `delta` wheel ticks to the window `id`, see `post_button`.
+#[allow(unreachable_code, unused_variables)]
+pub(crate) fn post_scroll(id: u64, axis: ScrollAxis, delta: i32, x: i32, y: i32) -> io::Result<()> {
+    #[cfg(all(windows, feature = "backend-win32"))]
+    return win32::post_scroll(id, axis, delta, x, y);
+
+    #[cfg(all(target_os = "linux", feature = "backend-x11"))]
+    return x11::post_scroll(id, axis, delta, x, y);
+
+    Err(unsupported())
+}
+
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no window lookup for this platform was enabled at build time",
+    )
+}
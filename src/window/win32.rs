@@ -0,0 +1,219 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::WindowInfo;
+use crate::input_backend::{MouseButton, ScrollAxis};
+use std::ffi::OsString;
+use std::io;
+use std::os::windows::ffi::OsStringExt;
+use std::path::Path;
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE, UINT, WPARAM};
+use winapi::shared::windef::{HWND, POINT};
+use winapi::um::{handleapi, processthreadsapi, winbase, winnt, winuser};
+
+unsafe extern "system" fn collect_window(hwnd: HWND, windows: LPARAM) -> BOOL {
+    (*(windows as *mut Vec<HWND>)).push(hwnd);
+    TRUE
+}
+
+/// Whether `hwnd` is shown and not minimized.
+fn is_shown(hwnd: HWND) -> bool {
+    unsafe { winuser::IsWindowVisible(hwnd) != 0 && winuser::IsIconic(hwnd) == 0 }
+}
+
+fn read_string(read: impl FnOnce(*mut u16, i32) -> i32) -> String {
+    let mut buffer = [0u16; 512];
+    let length = read(buffer.as_mut_ptr(), buffer.len() as i32).max(0) as usize;
+    String::from_utf16_lossy(&buffer[..length])
+}
+
+/// File name of the executable of the process that owns `hwnd`.
+fn process_name(hwnd: HWND) -> String {
+    let mut pid: DWORD = 0;
+    unsafe { winuser::GetWindowThreadProcessId(hwnd, &mut pid) };
+    let process = unsafe {
+        processthreadsapi::OpenProcess(winnt::PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid)
+    };
+    if process.is_null() {
+        return String::new();
+    }
+    let mut buffer = [0u16; 1024];
+    let mut length = buffer.len() as DWORD;
+    let result = unsafe {
+        winbase::QueryFullProcessImageNameW(process, 0, buffer.as_mut_ptr(), &mut length)
+    };
+    unsafe { handleapi::CloseHandle(process) };
+    if result == 0 {
+        return String::new();
+    }
+    let path = OsString::from_wide(&buffer[..length as usize]);
+    Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn info(hwnd: HWND) -> WindowInfo {
+    WindowInfo {
+        id: hwnd as usize as u64,
+        title: read_string(|buffer, length| unsafe {
+            winuser::GetWindowTextW(hwnd, buffer, length)
+        }),
+        class: read_string(|buffer, length| unsafe {
+            winuser::GetClassNameW(hwnd, buffer, length)
+        }),
+        process: process_name(hwnd),
+    }
+}
+
+pub fn windows() -> io::Result<Vec<WindowInfo>> {
+    let mut handles: Vec<HWND> = Vec::new();
+    if unsafe {
+        winuser::EnumWindows(
+            Some(collect_window),
+            &mut handles as *mut Vec<HWND> as LPARAM,
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(handles
+        .into_iter()
+        .filter(|hwnd| is_shown(*hwnd))
+        .map(info)
+        .collect())
+}
+
+pub fn foreground() -> io::Result<Option<WindowInfo>> {
+    let hwnd = unsafe { winuser::GetForegroundWindow() };
+    if hwnd.is_null() || !is_shown(hwnd) {
+        return Ok(None);
+    }
+    Ok(Some(info(hwnd)))
+}
+
+pub fn client_origin(id: u64) -> io::Result<Option<(i32, i32)>> {
+    let hwnd = id as usize as HWND;
+    if unsafe { winuser::IsWindow(hwnd) } == 0 || !is_shown(hwnd) {
+        return Ok(None);
+    }
+    let mut point = POINT { x: 0, y: 0 };
+    if unsafe { winuser::ClientToScreen(hwnd, &mut point) } == 0 {
+        return Ok(None);
+    }
+    Ok(Some((point.x, point.y)))
+}
+
+/// Deepest visible and enabled child of `hwnd` at `point` of its client area,
+/// and the point in the client area of that child.
+fn child_at(mut hwnd: HWND, mut point: POINT) -> (HWND, POINT) {
+    loop {
+        let child = unsafe {
+            winuser::ChildWindowFromPointEx(
+                hwnd,
+                point,
+                winuser::CWP_SKIPINVISIBLE
+                    | winuser::CWP_SKIPDISABLED
+                    | winuser::CWP_SKIPTRANSPARENT,
+            )
+        };
+        if child.is_null() || child == hwnd {
+            return (hwnd, point);
+        }
+        unsafe { winuser::MapWindowPoints(hwnd, child, &mut point, 1) };
+        hwnd = child;
+    }
+}
+
+/// Packs a point into the `LPARAM` of a mouse message.
+fn point_lparam(point: POINT) -> LPARAM {
+    ((point.y as u16 as u32) << 16 | point.x as u16 as u32) as LPARAM
+}
+
+/// Posts a message to `hwnd`, a window that no longer exists is `NotFound`.
+fn post(hwnd: HWND, message: UINT, wparam: WPARAM, lparam: LPARAM) -> io::Result<()> {
+    if unsafe { winuser::PostMessageW(hwnd, message, wparam, lparam) } == 0 {
+        if unsafe { winuser::IsWindow(hwnd) } == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "the window no longer exists",
+            ));
+        }
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Whether the class of `hwnd` asks for double-click messages, which the
+/// system only sends to such windows.
+fn accepts_double_clicks(hwnd: HWND) -> bool {
+    let style = unsafe { winuser::GetClassLongPtrW(hwnd, winuser::GCL_STYLE) };
+    style as UINT & winuser::CS_DBLCLKS != 0
+}
+
+pub fn post_button(
+    id: u64,
+    button: MouseButton,
+    is_pressed: bool,
+    is_double_click: bool,
+    x: i32,
+    y: i32,
+) -> io::Result<()> {
+    let (hwnd, point) = child_at(id as usize as HWND, POINT { x, y });
+    // The high word of the side button messages tells the buttons apart.
+    let (message, wparam) = match (button, is_pressed) {
+        (MouseButton::Left, true) => (winuser::WM_LBUTTONDOWN, winuser::MK_LBUTTON),
+        (MouseButton::Left, false) => (winuser::WM_LBUTTONUP, 0),
+        (MouseButton::Right, true) => (winuser::WM_RBUTTONDOWN, winuser::MK_RBUTTON),
+        (MouseButton::Right, false) => (winuser::WM_RBUTTONUP, 0),
+        (MouseButton::Middle, true) => (winuser::WM_MBUTTONDOWN, winuser::MK_MBUTTON),
+        (MouseButton::Middle, false) => (winuser::WM_MBUTTONUP, 0),
+        (MouseButton::X1, true) => (
+            winuser::WM_XBUTTONDOWN,
+            winuser::MK_XBUTTON1 | (winuser::XBUTTON1 as usize) << 16,
+        ),
+        (MouseButton::X1, false) => (winuser::WM_XBUTTONUP, (winuser::XBUTTON1 as usize) << 16),
+        (MouseButton::X2, true) => (
+            winuser::WM_XBUTTONDOWN,
+            winuser::MK_XBUTTON2 | (winuser::XBUTTON2 as usize) << 16,
+        ),
+        (MouseButton::X2, false) => (winuser::WM_XBUTTONUP, (winuser::XBUTTON2 as usize) << 16),
+    };
+    let message = if is_pressed && is_double_click && accepts_double_clicks(hwnd) {
+        match button {
+            MouseButton::Left => winuser::WM_LBUTTONDBLCLK,
+            MouseButton::Right => winuser::WM_RBUTTONDBLCLK,
+            MouseButton::Middle => winuser::WM_MBUTTONDBLCLK,
+            MouseButton::X1 | MouseButton::X2 => winuser::WM_XBUTTONDBLCLK,
+        }
+    } else {
+        message
+    };
+    post(hwnd, message, wparam as WPARAM, point_lparam(point))
+}
+
+pub fn post_scroll(id: u64, axis: ScrollAxis, delta: i32, x: i32, y: i32) -> io::Result<()> {
+    let (hwnd, mut point) = child_at(id as usize as HWND, POINT { x, y });
+    // Unlike the button messages, wheel messages carry screen coordinates.
+    unsafe { winuser::ClientToScreen(hwnd, &mut point) };
+    let message = match axis {
+        ScrollAxis::Vertical => winuser::WM_MOUSEWHEEL,
+        ScrollAxis::Horizontal => winuser::WM_MOUSEHWHEEL,
+    };
+    let distance = delta.saturating_mul(winuser::WHEEL_DELTA.into()) as u16 as WPARAM;
+    post(hwnd, message, distance << 16, point_lparam(point))
+}
@@ -0,0 +1,314 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::WindowInfo;
+use crate::input_backend::{MouseButton, ScrollAxis};
+use crate::x11::{self, reply_error, x11_error};
+use lazy_static::lazy_static;
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use x11rb::connection::Connection;
+use x11rb::errors::ReplyError;
+use x11rb::protocol::xproto::{
+    self, AtomEnum, ButtonPressEvent, ConnectionExt as _, EventMask, KeyButMask, MapState, Window,
+};
+use x11rb::rust_connection::RustConnection;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST,
+        _NET_CLIENT_LIST_STACKING,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        UTF8_STRING,
+    }
+}
+
+/// Connection to `$DISPLAY` shared by the lookups.
+struct Session {
+    connection: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+lazy_static! {
+    /// Opened on the first lookup and again after the connection broke.
+    static ref SESSION: Mutex<Option<Session>> = Mutex::new(None);
+}
+
+impl Session {
+    fn connect() -> io::Result<Self> {
+        let (connection, screen_number) = x11rb::connect(None).map_err(x11_error)?;
+        let root = connection.setup().roots[screen_number].root;
+        let atoms = Atoms::new(&connection)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        Ok(Self {
+            connection,
+            root,
+            atoms,
+        })
+    }
+
+    fn property(
+        &self,
+        window: Window,
+        property: impl Into<xproto::Atom>,
+        kind: impl Into<xproto::Atom>,
+    ) -> Result<xproto::GetPropertyReply, ReplyError> {
+        self.connection
+            .get_property(false, window, property, kind, 0, u32::MAX)?
+            .reply()
+    }
+
+    /// Client windows of the window manager from the bottom to the top, or
+    /// the children of the root window without an EWMH window manager.
+    fn client_list(&self) -> Result<Vec<Window>, ReplyError> {
+        for property in [
+            self.atoms._NET_CLIENT_LIST_STACKING,
+            self.atoms._NET_CLIENT_LIST,
+        ] {
+            let windows: Vec<_> = self
+                .property(self.root, property, AtomEnum::WINDOW)?
+                .value32()
+                .into_iter()
+                .flatten()
+                .collect();
+            if !windows.is_empty() {
+                return Ok(windows);
+            }
+        }
+        Ok(self.connection.query_tree(self.root)?.reply()?.children)
+    }
+
+    /// Describes `window`, `None` when it is not mapped.
+    fn info(&self, window: Window) -> Result<Option<WindowInfo>, ReplyError> {
+        let attributes = self.connection.get_window_attributes(window)?.reply()?;
+        if attributes.map_state != MapState::VIEWABLE {
+            return Ok(None);
+        }
+        let mut title = self.property(window, self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING)?;
+        if title.value.is_empty() {
+            title = self.property(window, AtomEnum::WM_NAME, AtomEnum::ANY)?;
+        }
+        // WM_CLASS holds the instance and the class name, both terminated by
+        // a null byte.
+        let class = self.property(window, AtomEnum::WM_CLASS, AtomEnum::STRING)?;
+        let class = class
+            .value
+            .split(|byte| *byte == 0)
+            .nth(1)
+            .map(|class| String::from_utf8_lossy(class).into_owned())
+            .unwrap_or_default();
+        let process = self
+            .property(window, self.atoms._NET_WM_PID, AtomEnum::CARDINAL)?
+            .value32()
+            .and_then(|mut pid| pid.next())
+            .map(process_name)
+            .unwrap_or_default();
+        Ok(Some(WindowInfo {
+            id: window.into(),
+            title: String::from_utf8_lossy(&title.value).into_owned(),
+            class,
+            process,
+        }))
+    }
+}
+
+/// Name of the executable of a local process, the name of its command when
+/// the executable cannot be read.
+fn process_name(pid: u32) -> String {
+    let proc_dir = Path::new("/proc").join(pid.to_string());
+    fs::read_link(proc_dir.join("exe"))
+        .ok()
+        .and_then(|exe| Some(exe.file_name()?.to_string_lossy().into_owned()))
+        .or_else(|| {
+            fs::read_to_string(proc_dir.join("comm"))
+                .ok()
+                .map(|comm| comm.trim_end().to_owned())
+        })
+        .unwrap_or_default()
+}
+
+/// Runs `lookup` on the shared connection. Errors of the X server, such as a
+/// window destroyed meanwhile, are left to `lookup` to handle.
+fn with_session<T>(lookup: impl FnOnce(&Session) -> Result<T, ReplyError>) -> io::Result<T> {
+    let mut lock = SESSION.lock().unwrap();
+    if lock.is_none() {
+        *lock = Some(Session::connect()?);
+    }
+    let result = lookup(lock.as_ref().unwrap());
+    if let Err(ReplyError::ConnectionError(_)) = result {
+        *lock = None;
+    }
+    result.map_err(reply_error)
+}
+
+pub fn windows() -> io::Result<Vec<WindowInfo>> {
+    with_session(|session| {
+        let mut windows = Vec::new();
+        for window in session.client_list()?.into_iter().rev() {
+            match session.info(window) {
+                Ok(Some(info)) => windows.push(info),
+                Ok(None) | Err(ReplyError::X11Error(_)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(windows)
+    })
+}
+
+pub fn foreground() -> io::Result<Option<WindowInfo>> {
+    with_session(|session| {
+        let window = session
+            .property(
+                session.root,
+                session.atoms._NET_ACTIVE_WINDOW,
+                AtomEnum::WINDOW,
+            )?
+            .value32()
+            .and_then(|mut windows| windows.next())
+            .filter(|window| *window != x11rb::NONE);
+        match window.map(|window| session.info(window)) {
+            Some(Ok(info)) => Ok(info),
+            None | Some(Err(ReplyError::X11Error(_))) => Ok(None),
+            Some(Err(error)) => Err(error),
+        }
+    })
+}
+
+pub fn client_origin(id: u64) -> io::Result<Option<(i32, i32)>> {
+    let window = match Window::try_from(id) {
+        Ok(window) => window,
+        Err(_) => return Ok(None),
+    };
+    with_session(|session| {
+        let origin = || -> Result<_, ReplyError> {
+            let attributes = session.connection.get_window_attributes(window)?;
+            let reply = session
+                .connection
+                .translate_coordinates(window, session.root, 0, 0)?
+                .reply()?;
+            Ok((attributes.reply()?.map_state == MapState::VIEWABLE)
+                .then(|| (reply.dst_x.into(), reply.dst_y.into())))
+        };
+        match origin() {
+            Err(ReplyError::X11Error(_)) => Ok(None),
+            result => result,
+        }
+    })
+}
+
+/// Sends a press or release of `button` to the deepest child of `window` at
+/// `x`, `y` of its area. Some applications ignore sent events.
+fn send_button(
+    session: &Session,
+    window: Window,
+    button: u8,
+    is_pressed: bool,
+    x: i16,
+    y: i16,
+) -> Result<(), ReplyError> {
+    let mut target = window;
+    let (event_x, event_y) = loop {
+        let reply = session
+            .connection
+            .translate_coordinates(window, target, x, y)?
+            .reply()?;
+        if reply.child == x11rb::NONE {
+            break (reply.dst_x, reply.dst_y);
+        }
+        target = reply.child;
+    };
+    let root = session
+        .connection
+        .translate_coordinates(window, session.root, x, y)?
+        .reply()?;
+    // The state holds the buttons that were down before the event.
+    let state = if is_pressed {
+        KeyButMask::from(0u16)
+    } else {
+        match button {
+            1 => KeyButMask::BUTTON1,
+            2 => KeyButMask::BUTTON2,
+            3 => KeyButMask::BUTTON3,
+            4 => KeyButMask::BUTTON4,
+            5 => KeyButMask::BUTTON5,
+            _ => KeyButMask::from(0u16),
+        }
+    };
+    let (response_type, mask) = if is_pressed {
+        (xproto::BUTTON_PRESS_EVENT, EventMask::BUTTON_PRESS)
+    } else {
+        (xproto::BUTTON_RELEASE_EVENT, EventMask::BUTTON_RELEASE)
+    };
+    let event = ButtonPressEvent {
+        response_type,
+        detail: button,
+        sequence: 0,
+        time: x11rb::CURRENT_TIME,
+        root: session.root,
+        event: target,
+        child: x11rb::NONE,
+        root_x: root.dst_x,
+        root_y: root.dst_y,
+        event_x,
+        event_y,
+        state,
+        same_screen: true,
+    };
+    session
+        .connection
+        .send_event(true, target, mask, event)?
+        .check()?;
+    session.connection.flush()?;
+    Ok(())
+}
+
+fn window_id(id: u64) -> io::Result<Window> {
+    Window::try_from(id).map_err(|_| io::Error::new(io::ErrorKind::NotFound, "no such window"))
+}
+
+pub fn post_button(
+    id: u64,
+    button: MouseButton,
+    is_pressed: bool,
+    x: i32,
+    y: i32,
+) -> io::Result<()> {
+    let window = window_id(id)?;
+    let (x, y) = x11::point(x, y)?;
+    with_session(|session| send_button(session, window, x11::button(button), is_pressed, x, y))
+}
+
+pub fn post_scroll(id: u64, axis: ScrollAxis, delta: i32, x: i32, y: i32) -> io::Result<()> {
+    let window = window_id(id)?;
+    let (x, y) = x11::point(x, y)?;
+    let button = x11::wheel_button(axis, delta);
+    with_session(|session| {
+        for _ in 0..delta.unsigned_abs() {
+            send_button(session, window, button, true, x, y)?;
+            send_button(session, window, button, false, x, y)?;
+        }
+        Ok(())
+    })
+}
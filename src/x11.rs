@@ -0,0 +1,80 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Conversions shared by the X11 backends, the window lookup and the X11
+//! key listener.
+
+use crate::input_backend::{MouseButton, ScrollAxis};
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::io;
+use strum::IntoEnumIterator;
+use x11rb::errors::ReplyError;
+use x11rb::protocol::ErrorKind;
+
+pub fn x11_error(error: impl Display) -> io::Error {
+    io::Error::other(error.to_string())
+}
+
+/// Like `x11_error`, but a window that no longer exists is `NotFound`.
+pub fn reply_error(error: ReplyError) -> io::Error {
+    match &error {
+        ReplyError::X11Error(x11_error) if x11_error.error_kind == ErrorKind::Window => {
+            io::Error::new(io::ErrorKind::NotFound, "the window no longer exists")
+        }
+        _ => x11_error(error),
+    }
+}
+
+/// Core button number of `button`.
+pub fn button(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Middle => 2,
+        MouseButton::Right => 3,
+        MouseButton::X1 => 8,
+        MouseButton::X2 => 9,
+    }
+}
+
+/// Mouse button of a core button number, `None` for the wheel and the
+/// buttons without a `MouseButton`.
+pub fn mouse_button(number: u32) -> Option<MouseButton> {
+    MouseButton::iter().find(|mouse_button| u32::from(button(*mouse_button)) == number)
+}
+
+/// Core button number that scrolls `axis` in the direction of `delta`, each
+/// press and release is one tick.
+pub fn wheel_button(axis: ScrollAxis, delta: i32) -> u8 {
+    match (axis, delta > 0) {
+        (ScrollAxis::Vertical, true) => 4,
+        (ScrollAxis::Vertical, false) => 5,
+        (ScrollAxis::Horizontal, false) => 6,
+        (ScrollAxis::Horizontal, true) => 7,
+    }
+}
+
+/// `x`, `y` as the 16-bit coordinates of the X11 protocol.
+pub fn point(x: i32, y: i32) -> io::Result<(i16, i16)> {
+    match (i16::try_from(x), i16::try_from(y)) {
+        (Ok(x), Ok(y)) => Ok((x, y)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{},{} is outside of the X11 screen", x, y),
+        )),
+    }
+}
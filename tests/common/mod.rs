@@ -0,0 +1,75 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Virtual X server for the tests of the X11 backends.
+
+use std::env;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Xvfb server, killed when dropped.
+pub struct Xvfb {
+    child: Child,
+    /// Name of its display, such as `:99`.
+    pub display: String,
+}
+
+impl Drop for Xvfb {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Xvfb {
+    /// Starts a server on a free display, panicking when Xvfb is not
+    /// installed or does not come up.
+    pub fn start() -> Self {
+        let number = (99..200)
+            .find(|number| {
+                !Path::new(&format!("/tmp/.X{}-lock", number)).exists()
+                    && !Path::new(&format!("/tmp/.X11-unix/X{}", number)).exists()
+            })
+            .expect("no display number is free");
+        let display = format!(":{}", number);
+        let child = Command::new("Xvfb")
+            .arg(&display)
+            .args(["-screen", "0", "1024x768x24", "-nolisten", "tcp"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("cannot start Xvfb");
+        let xvfb = Self { child, display };
+        let socket = format!("/tmp/.X11-unix/X{}", number);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !Path::new(&socket).exists() {
+            assert!(Instant::now() < deadline, "Xvfb did not start");
+            thread::sleep(Duration::from_millis(20));
+        }
+        xvfb
+    }
+
+    /// Points `$DISPLAY` to the server, for the code under test that opens
+    /// the default display. Only for test binaries with a single test, as
+    /// the environment is shared by their threads.
+    pub fn set_as_default(&self) {
+        env::set_var("DISPLAY", &self.display);
+        env::remove_var("WAYLAND_DISPLAY");
+    }
+}
@@ -0,0 +1,146 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Focus guard of the clicker on an Xvfb display, with the test standing in
+//! for the window manager that sets `_NET_ACTIVE_WINDOW`.
+
+#![cfg(all(target_os = "linux", feature = "backend-x11"))]
+
+mod common;
+
+use rsautoclicker::input_backend::{InputEvent, MouseButton, RecordingBackend};
+use rsautoclicker::{Clicker, ClickerEvent, Rate, Settings, StopReason};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum, ConnectionExt, CreateWindowAux, PropMode, Window, WindowClass,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+/// Maps a top-level window with the given title.
+fn create_window(connection: &RustConnection, root: Window, title: &str) -> Window {
+    let window = connection.generate_id().unwrap();
+    connection
+        .create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            100,
+            100,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )
+        .unwrap();
+    connection
+        .change_property8(
+            PropMode::REPLACE,
+            window,
+            AtomEnum::WM_NAME,
+            AtomEnum::STRING,
+            title.as_bytes(),
+        )
+        .unwrap();
+    connection.map_window(window).unwrap();
+    window
+}
+
+/// Does what a window manager does when `window` gets the focus.
+fn activate(connection: &RustConnection, root: Window, window: Window) {
+    let active_window = connection
+        .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+        .unwrap()
+        .reply()
+        .unwrap()
+        .atom;
+    connection
+        .change_property32(
+            PropMode::REPLACE,
+            root,
+            active_window,
+            AtomEnum::WINDOW,
+            &[window],
+        )
+        .unwrap();
+    connection.get_input_focus().unwrap().reply().unwrap();
+}
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn clicking_pauses_while_another_window_has_the_focus() {
+    let xvfb = common::Xvfb::start();
+    xvfb.set_as_default();
+    let (connection, screen_number) = x11rb::connect(None).unwrap();
+    let root = connection.setup().roots[screen_number].root;
+    let game = create_window(&connection, root, "Game");
+    let other = create_window(&connection, root, "Other");
+    activate(&connection, root, other);
+
+    let backend = RecordingBackend::new();
+    let clicker = Clicker::with_backend(
+        Settings {
+            click_rate: Rate::Period(Duration::from_millis(10).into()),
+            focus_window: Some("Game".parse().unwrap()),
+            ..Default::default()
+        },
+        Box::new(backend.clone()),
+    );
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    clicker.set_listener(Some(Arc::new(move |event| {
+        let _ = sender.lock().unwrap().send(event);
+    })));
+    let wait_for = |expected: ClickerEvent| {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while let Ok(event) =
+            receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            if event == expected {
+                return;
+            }
+        }
+        panic!("{:?} was not received", expected);
+    };
+
+    clicker.start();
+    wait_for(ClickerEvent::FocusLost);
+    thread::sleep(Duration::from_millis(100));
+    assert!(backend.events().is_empty());
+
+    activate(&connection, root, game);
+    wait_for(ClickerEvent::Resumed);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while backend.events().is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+        backend.events().first().map(|recorded| recorded.event),
+        Some(InputEvent::Press(MouseButton::Left))
+    );
+
+    activate(&connection, root, other);
+    wait_for(ClickerEvent::FocusLost);
+    clicker.stop();
+    wait_for(ClickerEvent::Stopped(StopReason::Requested));
+}
@@ -0,0 +1,137 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Targets relative to a window on an Xvfb display, which the clicker looks
+//! up again when it goes away.
+
+#![cfg(all(target_os = "linux", feature = "backend-x11"))]
+
+mod common;
+
+use rsautoclicker::input_backend::{InputEvent, RecordingBackend};
+use rsautoclicker::{Clicker, ClickerEvent, Rate, Settings, StopReason, Target};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum, ConnectionExt, CreateWindowAux, PropMode, Window, WindowClass,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+/// Maps a top-level window titled "Game" at `x`, `y`.
+fn create_window(connection: &RustConnection, root: Window, x: i16, y: i16) -> Window {
+    let window = connection.generate_id().unwrap();
+    connection
+        .create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            x,
+            y,
+            200,
+            100,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )
+        .unwrap();
+    connection
+        .change_property8(
+            PropMode::REPLACE,
+            window,
+            AtomEnum::WM_NAME,
+            AtomEnum::STRING,
+            b"Game",
+        )
+        .unwrap();
+    connection.map_window(window).unwrap();
+    connection.get_input_focus().unwrap().reply().unwrap();
+    window
+}
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn targets_follow_the_window_until_it_is_gone() {
+    let xvfb = common::Xvfb::start();
+    xvfb.set_as_default();
+    let (connection, screen_number) = x11rb::connect(Some(&xvfb.display)).unwrap();
+    let root = connection.setup().roots[screen_number].root;
+    let window = create_window(&connection, root, 100, 50);
+
+    let backend = RecordingBackend::new();
+    let clicker = Clicker::with_backend(
+        Settings {
+            click_rate: Rate::Period(Duration::from_millis(10).into()),
+            target_window: Some("Game".parse().unwrap()),
+            targets: vec![Target::new(10, 20)],
+            ..Default::default()
+        },
+        Box::new(backend.clone()),
+    );
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    clicker.set_listener(Some(Arc::new(move |event| {
+        let _ = sender.lock().unwrap().send(event);
+    })));
+    let wait_for = |expected: ClickerEvent| {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while let Ok(event) =
+            receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            if event == expected {
+                return;
+            }
+        }
+        panic!("{:?} was not received", expected);
+    };
+    let wait_for_move = || {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let moved = backend
+                .events()
+                .iter()
+                .find_map(|recorded| match recorded.event {
+                    InputEvent::MoveTo { x, y } => Some((x, y)),
+                    _ => None,
+                });
+            if moved.is_some() || Instant::now() >= deadline {
+                return moved;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    };
+
+    clicker.start();
+    assert_eq!(wait_for_move(), Some((110, 70)));
+
+    connection.destroy_window(window).unwrap();
+    connection.get_input_focus().unwrap().reply().unwrap();
+    wait_for(ClickerEvent::WindowLost);
+    backend.clear();
+    thread::sleep(Duration::from_millis(100));
+    assert!(backend.events().is_empty());
+
+    create_window(&connection, root, 300, 200);
+    wait_for(ClickerEvent::Resumed);
+    assert_eq!(wait_for_move(), Some((310, 220)));
+    clicker.stop();
+    wait_for(ClickerEvent::Stopped(StopReason::Requested));
+}
@@ -0,0 +1,105 @@
+/*
+*   Copyright (c) 2021 trickybestia
+
+*   This program is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+
+*   This program is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+
+*   You should have received a copy of the GNU General Public License
+*   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Clicks of the XTest backend as an X11 client sees them.
+
+#![cfg(all(target_os = "linux", feature = "backend-x11"))]
+
+mod common;
+
+use rsautoclicker::input_backend::XTestBackend;
+use rsautoclicker::{Clicker, ClickerEvent, Rate, Settings, StopReason, Target};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt, CreateWindowAux, EventMask, WindowClass};
+use x11rb::protocol::Event;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn timed_run_presses_the_button_once_per_click() {
+    let xvfb = common::Xvfb::start();
+    xvfb.set_as_default();
+    let (connection, screen_number) = x11rb::connect(None).unwrap();
+    let screen = &connection.setup().roots[screen_number];
+    let window = connection.generate_id().unwrap();
+    connection
+        .create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            screen.width_in_pixels,
+            screen.height_in_pixels,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            &CreateWindowAux::new()
+                .override_redirect(1)
+                .event_mask(EventMask::BUTTON_PRESS),
+        )
+        .unwrap();
+    connection.map_window(window).unwrap();
+    connection.get_input_focus().unwrap().reply().unwrap();
+
+    let clicker = Clicker::with_backend(
+        Settings {
+            click_rate: Rate::Period(Duration::from_millis(10).into()),
+            time_limit: Some(Duration::from_millis(300)),
+            targets: vec![Target::new(10, 10)],
+            ..Default::default()
+        },
+        Box::new(XTestBackend::new().unwrap()),
+    );
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    clicker.set_listener(Some(Arc::new(move |event| {
+        if let ClickerEvent::Stopped(reason) = event {
+            let _ = sender.lock().unwrap().send(reason);
+        }
+    })));
+    clicker.start();
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(5)),
+        Ok(StopReason::TimeLimitReached)
+    );
+    let clicks = clicker.timing().clicks;
+    assert!(clicks > 0);
+
+    // The backend does not wait for its requests to be processed.
+    let mut presses = 0;
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while presses < clicks && Instant::now() < deadline {
+        match connection.poll_for_event().unwrap() {
+            Some(Event::ButtonPress(event)) => {
+                assert_eq!(event.detail, 1);
+                assert_eq!((event.event_x, event.event_y), (10, 10));
+                presses += 1;
+            }
+            Some(_) => {}
+            None => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+    thread::sleep(Duration::from_millis(100));
+    while let Some(event) = connection.poll_for_event().unwrap() {
+        assert!(!matches!(event, Event::ButtonPress(_)));
+    }
+    assert_eq!(presses, clicks);
+}